@@ -28,7 +28,7 @@ mod tests {
         };
 
         b.iter(|| {
-            Marker::deno_bindgen(input.clone());
+            Marker::deno_bindgen(TokenStream::new(), input.clone());
         });
     }
 }
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
 use cargo_metadata::{Message, MetadataCommand};
+use deno_bindgen2_common::{EnumTable, InternTable, Marker, StructTable};
 
 
 /// LIMITATIONS
@@ -133,7 +134,15 @@ impl Cargo {
         content
     }
 
-    pub fn build(pkg_name: &str, release: bool, mut cfgs: Vec<&str>) -> PathBuf {
+    pub fn build(
+        pkg_name: &str,
+        release: bool,
+        mut cfgs: Vec<&str>,
+        intern_table: &InternTable,
+        struct_table: &StructTable,
+        enum_table: &EnumTable,
+        js_string: bool,
+    ) -> PathBuf {
         let mut cmd = Command::new("cargo");
         cmd.arg("+nightly")
             .arg("build")
@@ -145,11 +154,20 @@ impl Cargo {
             .stdout(Stdio::piped());
 
         cfgs.push("deno_bindgen");
-        let cfgs = cfgs.iter().map(|cfg| {
-            format!("--cfg {} ", cfg)
-        }).collect::<String>();
+        let cfgs = cfgs
+            .iter()
+            .map(|cfg| format!("--cfg {} ", cfg))
+            .collect::<String>();
 
         cmd.env("RUSTFLAGS", cfgs);
+        cmd.env(InternTable::ENV, intern_table.to_env());
+        cmd.env(StructTable::ENV, struct_table.to_env());
+        cmd.env(EnumTable::ENV, enum_table.to_env());
+        if js_string {
+            // scoped to this package, as the variable is seen by every crate in
+            // the build
+            cmd.env(Marker::JS_STRING_ENV, pkg_name);
+        }
 
         if release {
             cmd.arg("--release");
@@ -225,7 +243,15 @@ mod tests {
         let metadata = Cargo::get_metadata();
         // let dylib_path = Cargo::build(pkg_name, pkg_rel_path, release)
 
-        let dylib_path = Cargo::build(&metadata.pkg_name, false, vec![]);
+        let dylib_path = Cargo::build(
+            &metadata.pkg_name,
+            false,
+            vec![],
+            &InternTable::default(),
+            &StructTable::default(),
+            &EnumTable::default(),
+            false,
+        );
         dbg!(&dylib_path);
         /*
                 successes:
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use clap::Parser;
-use deno_bindgen2_common::CodegenOpts;
+use deno_bindgen2_common::{CodegenOpts, RenameRule};
 
 
 // interactive mode if no subcommand was passed
@@ -54,6 +54,24 @@ pub struct Cli {
     #[arg(short = 'm', long, group = "link")]
     embedded: Option<PathBuf>,
 
+    /// Accept and return javascript strings for the `&str` and `String` values
+    /// of every function, instead of the `RustStr` and `RustString` wrappers.
+    /// Use the `js_string` attribute to enable this for a single function
+    #[arg(short = 's', long)]
+    pub js_string: bool,
+
+    /// Mirror the rust module tree with typescript namespaces, so that the
+    /// functions and constants of different modules do not collide. Types are
+    /// declared in the namespace of their module as well
+    #[arg(long)]
+    pub namespaces: bool,
+
+    /// Naming convention of the functions, methods, fields and constants in
+    /// typescript, one of `camelCase`, `PascalCase` or `snake_case`. Use the
+    /// `#[deno_bindgen(rename = "name")]` attribute to rename a single item
+    #[arg(long)]
+    pub rename_all: Option<RenameRule>,
+
     /// Disables source code expansion and module scanning
     #[arg(short = 'n', long)]
     no_expand: bool,
@@ -68,8 +86,14 @@ pub struct Cli {
 }
 
 impl Cli {
-    pub fn to_codegen_opts(&self, file_name: String, dylib_path: PathBuf) -> CodegenOpts {
+    pub fn to_codegen_opts(
+        &self,
+        crate_name: String,
+        file_name: String,
+        dylib_path: PathBuf,
+    ) -> CodegenOpts {
         CodegenOpts {
+            crate_name,
             file_name,
             dylib_path: dylib_path
                 .to_str()
@@ -78,6 +102,9 @@ impl Cli {
             lazy: self.lazy,
             extended: self.extended,
             embedded: self.embedded.clone(),
+            js_string: self.js_string,
+            namespaces: self.namespaces,
+            rename_all: self.rename_all,
         }
     }
 }
@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use deno_bindgen2_common::{File, TsModule};
+use deno_bindgen2_common::{EnumTable, File, InternTable, StructTable, TsModule};
 
 mod cargo;
 mod interface;
@@ -65,7 +65,23 @@ fn main() -> Result<()> {
         cfgs.push("deno_bindgen_rust_string");
     }
 
-    let dylib_path = Cargo::build(&metadata.pkg_name, args.release, cfgs);
+    // intern the tuple and `Vec<T>` types of the whole crate so that the macro
+    // emits their accessor symbols only once
+    let intern_table = InternTable::collect(&file.items);
+    // `repr(C)` structs whose fields can all be passed by value
+    let struct_table = StructTable::collect(&file.items);
+    // fieldless enums passed as their integer repr
+    let enum_table = EnumTable::collect(&file.items);
+
+    let dylib_path = Cargo::build(
+        &metadata.pkg_name,
+        args.release,
+        cfgs,
+        &intern_table,
+        &struct_table,
+        &enum_table,
+        args.js_string,
+    );
 
     let file_name;
     let out_path;
@@ -86,7 +102,9 @@ fn main() -> Result<()> {
         std::fs::create_dir_all(&out_path).expect("failed to create `dist` dir");
     }
 
+    // symbols are exported under the crate name, as seen by `module_path!()`
     let opts = args.to_codegen_opts(
+        metadata.lib_name.replace('-', "_"),
         file_name.to_string(),
         metadata.strip_workspace_path(&dylib_path),
     );
@@ -1,59 +1,227 @@
 use std::collections::BTreeMap;
 
 use crate::deno::util::*;
-use crate::deno::{FfiFunction, FfiType, TsMethod, TsModule};
-use crate::rust::ItemImpl;
+use crate::deno::{catch_panic, FfiFunction, FfiType, RustType, StructDefs, TsMethod, TsModule};
+use crate::rust::{ItemImpl, ItemStruct, Protocol};
 
 #[derive(Clone, Debug)]
 pub struct TsClass {
+    /// name of the class in typescript, within its namespace
+    pub path:    TsPath,
     pub methods: Vec<TsMethod>,
+    /// members printed after the methods by their name, i.e. the helpers of an
+    /// enum or the protocols of well-known traits
+    pub members: BTreeMap<String, TokenStream>,
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct ClassDefs {
     pub store: BTreeMap<Ident, TsClass>,
+    /// doc comments of the types, printed on their class
+    pub docs:  BTreeMap<Ident, JsDoc>,
 }
 
 impl ItemImpl {
     pub fn unwrap(self, module: &mut TsModule) {
+        let protocol = self.protocol();
         let mut methods = Vec::new();
         for item in self.items {
             let method = item.unwrap(module);
             methods.push(method);
         }
 
-        if let Some(ts_class) = module.class_defs.store.get_mut(&self.self_ty) {
+        // methods of a struct passed by value are printed on its struct class
+        let by_value = StructDefs::declare(&self.self_ty, module).is_some();
+
+        let class = module.type_name(&self.self_ty);
+        let (drop, _) = ClassDefs::drop_symbol(module, &self.self_ty);
+        let members = match protocol {
+            Some(protocol) => protocol_members(protocol, &mut methods, &drop, &class, by_value),
+            None => Vec::new(),
+        };
+        let class_members = if by_value {
+            let ts_struct = module.struct_defs.store.get_mut(&self.self_ty).unwrap();
+            ts_struct.methods.append(&mut methods);
+            &mut ts_struct.members
+        } else {
+            ClassDefs::push_methods(module, self.self_ty.clone(), methods);
+            let ts_class = module.class_defs.store.get_mut(&self.self_ty).unwrap();
+            &mut ts_class.members
+        };
+        for (name, member) in members {
+            // `Ord` takes precedence over `PartialOrd` for `compareTo()`
+            if protocol == Some(Protocol::PartialOrd) {
+                class_members.entry(name).or_insert(member);
+            } else {
+                class_members.insert(name, member);
+            }
+        }
+    }
+}
+
+/// Maps the exported function of a well-known trait onto the javascript
+/// protocol of a class. Comparisons take the other value as an instance of the
+/// class rather than as a reference
+fn protocol_members(
+    protocol: Protocol,
+    methods: &mut Vec<TsMethod>,
+    drop: &Ident,
+    class: &Ident,
+    by_value: bool,
+) -> Vec<(String, TokenStream)> {
+    let ptr = |value: TokenStream| {
+        if by_value {
+            quote! { #value.toBuffer() }
+        } else {
+            quote! { #value.ptr }
+        }
+    };
+    match protocol {
+        Protocol::Display => {
+            methods[0].ident = format_ident!("toString");
+            Vec::new()
+        },
+        Protocol::Debug => vec![(
+            r#"[Symbol.for("Deno.customInspect")]"#.to_string(),
+            quote! {
+                [Symbol.for("Deno.customInspect")](): string {
+                    return this.inspect();
+                }
+            },
+        )],
+        Protocol::Clone | Protocol::Default => Vec::new(),
+        // the rust iterator is dropped once it is exhausted
+        Protocol::Iterator => {
+            let RustType::Option(item) = &methods[0].output else {
+                unreachable!("the `next` method of an iterator returns an `Option`");
+            };
+            vec![("[Symbol.iterator]".to_string(), quote! {
+                *[Symbol.iterator](): Generator<#item> {
+                    for (let item = this.next(); item != null; item = this.next()) {
+                        yield item;
+                    }
+                    symbols.#drop(this.take());
+                }
+            })]
+        },
+        Protocol::PartialEq | Protocol::PartialOrd | Protocol::Ord => {
+            let method = methods.pop().unwrap();
+            let ffi_ident = &method.ffi_ident;
+            let (ident, output) = if protocol == Protocol::PartialEq {
+                (format_ident!("equals"), quote! { boolean })
+            } else {
+                (format_ident!("compareTo"), quote! { number })
+            };
+            let this = ptr(quote! { this });
+            let other = ptr(quote! { other });
+            let catch_panic = catch_panic(&quote! { out });
+            vec![(ident.to_string(), quote! {
+                #ident(other: #class): #output {
+                    const out = symbols.#ffi_ident(#this, #other);
+                    #catch_panic
+                    return out;
+                }
+            })]
+        },
+    }
+}
+
+impl ItemStruct {
+    /// Adds the field accessors of this struct to its class as typescript
+    /// `get` and `set` accessors
+    pub fn unwrap(self, module: &mut TsModule) {
+        // the fields of a struct passed by value are already part of its class
+        if StructDefs::declare(&self.ident, module).is_some() {
+            return;
+        }
+
+        module
+            .class_defs
+            .docs
+            .insert(self.ident.clone(), JsDoc::new(&self.attr));
+        let mut methods = Vec::new();
+        for (ident, item) in self.accessors() {
+            let mut method = item.unwrap(module);
+            method.ident = module.ts_name(&method.attr, &ident);
+            methods.push(method);
+        }
+        if !methods.is_empty() {
+            ClassDefs::push_methods(module, self.ident, methods);
+        }
+    }
+}
+
+impl ClassDefs {
+    /// The key and name of the symbol dropping a type. The drop shim is
+    /// expanded along with the type, in the module it is declared in
+    pub fn drop_symbol(module: &TsModule, self_ty: &Ident) -> (Ident, String) {
+        let drop = format_ident!("__{self_ty}__drop");
+        let mod_path = module
+            .type_paths
+            .get(self_ty)
+            .cloned()
+            .unwrap_or_else(|| module.mod_path.clone());
+        module.symbol(&mod_path, &drop)
+    }
+
+    /// Adds methods to the class of a type, declaring the class along with its
+    /// drop symbol if it does not exist yet
+    pub fn push_methods(module: &mut TsModule, self_ty: Ident, mut methods: Vec<TsMethod>) {
+        if let Some(ts_class) = module.class_defs.store.get_mut(&self_ty) {
+            let constructor = |method: &TsMethod| method.attr.has_constructor();
+            if ts_class.methods.iter().any(constructor) && methods.iter().any(constructor) {
+                panic!("`{self_ty}` has more than one `constructor`");
+            }
             ts_class.methods.append(&mut methods);
         } else {
+            let (drop, drop_name) = Self::drop_symbol(module, &self_ty);
             module.ffi_lib.interface.push_fn(FfiFunction {
-                ident:        format_ident!("__{}__drop", &self.self_ty.to_string()),
+                ident:        drop,
+                name:         drop_name,
                 inputs:       vec![FfiType::Pointer],
                 output:       FfiType::Void,
                 non_blocking: false,
             });
-            module
-                .class_defs
-                .store
-                .insert(self.self_ty, TsClass { methods });
+            module.class_defs.store.insert(self_ty.clone(), TsClass {
+                path: module.type_path(&self_ty),
+                methods,
+                members: BTreeMap::new(),
+            });
         }
     }
 }
 
 impl ToTokens for ClassDefs {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        for (ident, TsClass { methods }) in &self.store {
+        let mut decls = Vec::new();
+        for (
+            rust_ident,
+            TsClass {
+                path,
+                methods,
+                members,
+            },
+        ) in &self.store
+        {
+            let ident = &path.ident;
+            let members = members.values();
             let mut _methods = Vec::new();
             for method in methods {
+                let doc = &method.doc;
                 let method = method.print();
-                _methods.push(method);
+                _methods.push(quote! { #doc #method });
             }
+            let doc = self.docs.get(rust_ident);
 
-            tokens.extend(quote! {
+            decls.push((path.namespace.clone(), quote! {
+                #doc
                 export class #ident extends RustPrototype<#ident> {
                     #(#_methods)*
+                    #(#members)*
                 }
-            });
+            }));
         }
+        tokens.extend(print_namespaced(decls));
     }
 }
 
@@ -89,6 +257,141 @@ mod tests {
     }
 
 
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_accessors() {
+        let source = parse_quote!(
+            ItemStruct,
+            struct Counter {
+                #[deno_bindgen(get, set)]
+                count: u32,
+                #[deno_bindgen(get)]
+                name:  String,
+            }
+        );
+        let mut module = TsModule::default();
+        source.unwrap(&mut module);
+
+        let class_defs = TsFormat::format(module.class_defs.to_token_stream().to_string());
+        println!("{class_defs}");
+        assert!(class_defs.contains("get count(): RustU32 {"));
+        assert!(class_defs.contains("set count(arg_0: RustU32) {"));
+        assert!(class_defs.contains("get name(): RustRef<RustString> {"));
+        assert!(class_defs.contains("symbols.__Counter__set_count(ptr, arg_0, out_panic)"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_protocols() {
+        let mut module = TsModule::default();
+        for source in [
+            parse_quote!(ItemImpl, impl std::fmt::Display for Point {}),
+            parse_quote!(ItemImpl, impl fmt::Debug for Point {}),
+            parse_quote!(ItemImpl, impl Clone for Point {}),
+            parse_quote!(ItemImpl, impl PartialEq for Point {}),
+            parse_quote!(ItemImpl, impl Ord for Point {}),
+            parse_quote!(ItemImpl, impl PartialOrd for Point {}),
+            parse_quote!(ItemImpl, impl Default for Point {}),
+            parse_quote!(
+                ItemImpl,
+                impl Iterator for Point {
+                    type Item = f64;
+                }
+            ),
+            parse_quote!(
+                ItemImpl,
+                impl Shape for Point {
+                    fn area(&self) -> f64 {}
+                }
+            ),
+        ] {
+            source.unwrap(&mut module);
+        }
+
+        let class_defs = TsFormat::format(module.class_defs.to_token_stream().to_string());
+        println!("{class_defs}");
+        assert!(class_defs.contains("toString(): string {"));
+        assert!(class_defs.contains("inspect(): string {"));
+        assert!(class_defs.contains(r#"[Symbol.for("Deno.customInspect")](): string {"#));
+        assert!(class_defs.contains("clone(): Point {"));
+        assert!(class_defs.contains("equals(other: Point): boolean {"));
+        assert!(class_defs.contains("symbols.__Point__eq(this.ptr, other.ptr)"));
+        assert!(class_defs.contains("symbols.__Point__compare(this.ptr, other.ptr)"));
+        assert!(!class_defs.contains("symbols.__Point__partial_compare"));
+        assert!(class_defs.contains("static default(): Point {"));
+        assert!(class_defs.contains("area(): RustF64 {"));
+        assert!(class_defs.contains("next(): RustF64 | null {"));
+        assert!(class_defs.contains("*[Symbol.iterator](): Generator<RustF64> {"));
+        assert!(class_defs.contains("symbols.__Point__drop(this.take());"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_non_blocking_receiver() {
+        let source = parse_quote!(
+            ItemImpl,
+            impl Counter {
+                #[non_blocking]
+                fn add(&mut self, value: u32) -> u32 {}
+            }
+        );
+        let mut module = TsModule::default();
+        source.unwrap(&mut module);
+
+        let class_defs = TsFormat::format(module.class_defs.to_token_stream().to_string());
+        assert!(class_defs.contains("async add(arg_0: RustU32): Promise<RustU32> {"));
+        assert!(class_defs.contains("const ptr = this.lock();"));
+        assert!(
+            class_defs.contains("const out = await symbols.__Counter__add(ptr, arg_0, out_panic);")
+        );
+        assert!(class_defs.contains("throw new RustPanicError(message);"));
+        assert!(class_defs.contains("} finally {\n            this.unlock();"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_constructor() {
+        let source = parse_quote!(
+            ItemImpl,
+            impl Counter {
+                #[deno_bindgen(constructor)]
+                fn new(start: u32) -> Self {}
+                fn copy(&self) -> Counter {}
+            }
+        );
+        let mut module = TsModule::default();
+        source.unwrap(&mut module);
+
+        let class_defs = TsFormat::format(module.class_defs.to_token_stream().to_string());
+        assert!(class_defs.contains("constructor(arg_0: RustU32) {"));
+        assert!(class_defs.contains("const out = symbols.__Counter__new(arg_0);"));
+        assert!(class_defs.contains("super(out!);"));
+        assert!(!class_defs.contains("static new("));
+        assert!(class_defs.contains("return Counter.wrap(out!) as Counter;"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_getter_setter() {
+        let source = parse_quote!(
+            ItemImpl,
+            impl Label {
+                #[deno_bindgen(getter)]
+                fn len(&self) -> usize {}
+                #[deno_bindgen(setter)]
+                fn set_name(&mut self, value: String) {}
+            }
+        );
+        let mut module = TsModule::default();
+        source.unwrap(&mut module);
+
+        let class_defs = TsFormat::format(module.class_defs.to_token_stream().to_string());
+        assert!(class_defs.contains("get len(): RustUsize {"));
+        assert!(class_defs.contains("const out = symbols.__Label__len(this.ptr);"));
+        assert!(class_defs.contains("set name(arg_0: RustString) {"));
+        assert!(class_defs.contains("symbols.__Label__set_name(ptr, arg_0.take(), out_panic)!;"));
+    }
+
     #[test]
     #[cfg(feature = "cli")]
     fn test_print() {
@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+
+use crate::deno::util::*;
+use crate::deno::{ClassDefs, RustType, TsModule};
+use crate::rust::{ItemEnum, Variant};
+
+/// Container for the enums of a crate
+#[derive(Clone, Debug, Default)]
+pub struct EnumDefs {
+    /// C-like enums, printed as typescript enums with the same discriminant
+    /// values
+    pub store:  BTreeMap<TsPath, Vec<Variant>>,
+    /// enums passed as opaque pointers, printed as a discriminated union of
+    /// their variants named `XValue`
+    pub unions: BTreeMap<TsPath, Vec<TsVariant>>,
+    /// doc comments of the C-like enums
+    pub docs:   BTreeMap<TsPath, JsDoc>,
+}
+
+/// A variant in the discriminated union of an enum
+#[derive(Clone, Debug)]
+pub struct TsVariant {
+    pub ident:  Ident,
+    /// name of each field in the union, along with its getter and type
+    pub fields: Vec<(Ident, Ident, RustType)>,
+}
+
+impl ItemEnum {
+    pub fn unwrap(self, module: &mut TsModule) {
+        let doc = JsDoc::new(&self.attr);
+        // the typescript enums and unions are declared by their new name
+        let ts_path = module.type_path(&self.ident);
+        if self.c_like_repr().is_some() {
+            module.enum_defs.docs.insert(ts_path.clone(), doc);
+            module.enum_defs.store.insert(ts_path, self.variants);
+            return;
+        }
+        module.class_defs.docs.insert(self.ident.clone(), doc);
+
+        let mut methods = Vec::new();
+        for (ident, item) in self.helpers() {
+            let mut method = item.unwrap(module);
+            method.ident = ident;
+            methods.push(method);
+        }
+
+        let variants: Vec<TsVariant> = self
+            .variants
+            .iter()
+            .map(|variant| TsVariant {
+                ident:  variant.ident.clone(),
+                fields: variant
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| {
+                        let (name, getter) = match &field.ident {
+                            Some(ident) => (
+                                ident.clone(),
+                                format_ident!("get_{}_{ident}", variant.ident),
+                            ),
+                            None => (
+                                format_ident!("_{i}"),
+                                format_ident!("get_{}_{i}", variant.ident),
+                            ),
+                        };
+                        let method = methods
+                            .iter()
+                            .find(|method| method.ident == getter)
+                            .unwrap();
+                        (name, getter, method.output.clone())
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let members = print_helpers(&ts_path.ident, &variants);
+        ClassDefs::push_methods(module, self.ident.clone(), methods);
+        let ts_class = module.class_defs.store.get_mut(&self.ident).unwrap();
+        ts_class.members.extend(members);
+        module.enum_defs.unions.insert(ts_path, variants);
+    }
+}
+
+/// The class members of an enum passed as an opaque pointer
+///
+/// - `kind`, the name of its current variant
+/// - `toValue()`, which reads its fields into the discriminated union
+/// - `match(arms)`, which calls the arm of its current variant with its value
+fn print_helpers(ident: &Ident, variants: &[TsVariant]) -> Vec<(String, TokenStream)> {
+    let value_ty = format_ident!("{ident}Value");
+    let kinds: Vec<proc_macro2::Literal> = variants
+        .iter()
+        .map(|variant| proc_macro2::Literal::string(&variant.ident.to_string()))
+        .collect();
+    let values = variants.iter().zip(&kinds).map(|(variant, kind)| {
+        let names = variant.fields.iter().map(|(name, ..)| name);
+        let getters = variant.fields.iter().map(|(_, getter, _)| getter);
+        quote! { case #kind: return { kind: #kind, #(#names: this.#getters()),* }; }
+    });
+
+    vec![
+        ("kind".to_string(), quote! {
+            get kind(): #value_ty["kind"] {
+                return ([#(#kinds),*] as const)[this.discriminant()];
+            }
+        }),
+        ("toValue".to_string(), quote! {
+            toValue(): #value_ty {
+                switch (this.kind) {
+                    #(#values)*
+                }
+            }
+        }),
+        ("match".to_string(), quote! {
+            match<R>(arms: { [K in #value_ty["kind"]]: (value: Extract<#value_ty, { kind: K }>) => R }): R {
+                const value = this.toValue();
+                return (arms[value.kind] as (value: #value_ty) => R)(value);
+            }
+        }),
+    ]
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: print
+
+impl ToTokens for EnumDefs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let mut decls = Vec::new();
+        for (path, variants) in &self.store {
+            let ident = &path.ident;
+            let idents = variants.iter().map(|variant| &variant.ident);
+            let discriminants = variants
+                .iter()
+                .map(|variant| proc_macro2::Literal::i128_unsuffixed(variant.discriminant));
+            let doc = self.docs.get(path);
+
+            decls.push((path.namespace.clone(), quote! {
+                #doc
+                export enum #ident {
+                    #(#idents = #discriminants),*
+                }
+            }));
+        }
+
+        for (path, variants) in &self.unions {
+            let value_ty = format_ident!("{}Value", path.ident);
+            let variants = variants.iter().map(|variant| {
+                let kind = proc_macro2::Literal::string(&variant.ident.to_string());
+                let names = variant.fields.iter().map(|(name, ..)| name);
+                let types = variant.fields.iter().map(|(.., ty)| ty);
+                quote! { | { kind: #kind, #(#names: #types),* } }
+            });
+
+            decls.push((path.namespace.clone(), quote! {
+                export type #value_ty = #(#variants)*;
+            }));
+        }
+        tokens.extend(print_namespaced(decls));
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deno::{CodegenOpts, TsFormat};
+    use crate::rust::File;
+
+    #[test]
+    fn test_print() {
+        let file = File::parse_str(
+            quote! {
+                #[doc = "deno_bindgen"]
+                #[repr(i8)]
+                enum Mode { A = -1, B, C = 4 }
+
+                #[doc = "deno_bindgen"]
+                fn next(mode: Mode) -> Mode {}
+            }
+            .to_string()
+            .as_str(),
+        );
+        let module = TsModule::new(file, &CodegenOpts {
+            crate_name: "deno_bindgen2_test".to_string(),
+            file_name:  String::new(),
+            dylib_path: String::new(),
+            lazy:       false,
+            extended:   false,
+            embedded:   None,
+            js_string:  false,
+            namespaces: false,
+            rename_all: None,
+        });
+
+        let enum_defs = TsFormat::format(module.enum_defs.to_token_stream().to_string());
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        let ffi_lib = TsFormat::format(module.ffi_lib.to_token_stream().to_string());
+        println!("{ffi_lib}\n{enum_defs}\n{functions}");
+
+        assert!(enum_defs.contains("export enum Mode {\n    A = -1,\n    B = 0,\n    C = 4,\n}"));
+        assert!(functions.contains("export function next(arg_0: Mode): Mode {"));
+        assert!(functions.contains("return Number(out) as Mode;"));
+        assert!(ffi_lib.contains(
+            r#"__next: { name: "deno_bindgen2_test::__next", parameters: ["i8"], result: "i8" }"#
+        ));
+        assert!(module.user_defs.to_token_stream().is_empty());
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_print_union() {
+        let file = File::parse_str(
+            quote! {
+                #[doc = "deno_bindgen"]
+                enum Shape { Circle { r: f64 }, Named(String), Empty }
+
+                #[doc = "deno_bindgen"]
+                fn area(shape: &Shape) -> f64 {}
+            }
+            .to_string()
+            .as_str(),
+        );
+        let module = TsModule::new(file, &CodegenOpts {
+            crate_name: "deno_bindgen2_test".to_string(),
+            file_name:  String::new(),
+            dylib_path: String::new(),
+            lazy:       false,
+            extended:   false,
+            embedded:   None,
+            js_string:  false,
+            namespaces: false,
+            rename_all: None,
+        });
+
+        let enum_defs = TsFormat::format(module.enum_defs.to_token_stream().to_string());
+        let class_defs = TsFormat::format(module.class_defs.to_token_stream().to_string());
+        let ffi_lib = TsFormat::format(module.ffi_lib.to_token_stream().to_string());
+        println!("{ffi_lib}\n{enum_defs}\n{class_defs}");
+
+        assert!(enum_defs.contains(r#"kind: "Circle"; r: RustF64"#));
+        assert!(enum_defs.contains(r#"kind: "Named"; _0: RustRef<RustString>"#));
+        assert!(class_defs.contains("export class Shape extends RustPrototype<Shape> {"));
+        assert!(class_defs.contains("static Circle(arg_0: RustF64): Shape {"));
+        assert!(class_defs.contains("get kind(): ShapeValue[\"kind\"] {"));
+        assert!(class_defs.contains(r#"return { kind: "Circle", r: this.get_Circle_r() };"#));
+        assert!(ffi_lib.contains("__Shape__discriminant"));
+        assert!(ffi_lib.contains("__Shape__drop"));
+        assert!(module.user_defs.to_token_stream().is_empty());
+    }
+}
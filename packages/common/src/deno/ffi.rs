@@ -1,4 +1,8 @@
+use syn::parse::{ParseStream, Parser};
+
 use crate::deno::util::*;
+use crate::deno::TsModule;
+use crate::rust::{Type, TypeDynFn};
 
 /* -------------------------------------------------------------------------- */
 
@@ -24,24 +28,140 @@ pub enum FfiType {
     Buffer, /* cannot be constructed directly. must be passed through the utility modules for
              * conversion from opaque pointer to access internal buffer */
     FnPointer,
-    // Struct, // [!TODO] support for struct type
+    Struct(Vec<FfiType>),
+}
+
+impl FfiType {
+    /// The size and alignment of this type in a `#[repr(C)]` struct, assuming
+    /// a 64-bit target
+    pub fn layout(&self) -> (usize, usize) {
+        match self {
+            FfiType::Void => (0, 1),
+            FfiType::U8 | FfiType::I8 | FfiType::Bool => (1, 1),
+            FfiType::U16 | FfiType::I16 => (2, 2),
+            FfiType::U32 | FfiType::I32 | FfiType::F32 => (4, 4),
+            FfiType::U64
+            | FfiType::I64
+            | FfiType::F64
+            | FfiType::Usize
+            | FfiType::Isize
+            | FfiType::Pointer
+            | FfiType::Buffer
+            | FfiType::FnPointer => (8, 8),
+            FfiType::Struct(fields) => {
+                let (_, size, align) = Self::struct_layout(fields);
+                (size, align)
+            },
+        }
+    }
+
+    /// The field offsets, size and alignment of a `#[repr(C)]` struct
+    pub fn struct_layout(fields: &[FfiType]) -> (Vec<usize>, usize, usize) {
+        let mut offsets = Vec::new();
+        let mut size = 0usize;
+        let mut align = 1usize;
+        for field in fields {
+            let (field_size, field_align) = field.layout();
+            size = size.next_multiple_of(field_align);
+            offsets.push(size);
+            size += field_size;
+            align = align.max(field_align);
+        }
+        (offsets, size.next_multiple_of(align), align)
+    }
+
+    /// The `DataView` accessor of a numeric type, and whether it takes an
+    /// endianness argument
+    #[rustfmt::skip]
+    pub fn accessor(&self) -> (&'static str, bool) {
+        match self {
+            FfiType::U8                   => ("Uint8", false),
+            FfiType::I8                   => ("Int8", false),
+            FfiType::U16                  => ("Uint16", true),
+            FfiType::I16                  => ("Int16", true),
+            FfiType::U32                  => ("Uint32", true),
+            FfiType::I32                  => ("Int32", true),
+            FfiType::F32                  => ("Float32", true),
+            FfiType::F64                  => ("Float64", true),
+            FfiType::U64 | FfiType::Usize => ("BigUint64", true),
+            FfiType::I64 | FfiType::Isize => ("BigInt64", true),
+            _ => unreachable!("not a numeric type"),
+        }
+    }
 }
 
 // https://docs.deno.com/api/deno/~/Deno.ForeignFunction
 #[derive(Clone, Debug, PartialEq)]
 pub struct FfiFunction {
     pub ident:        Ident,
+    /// the name the symbol is exported under, qualified by the path of the
+    /// module its shim is expanded in
+    pub name:         String,
     pub inputs:       Vec<FfiType>,
     pub output:       FfiType,
-    pub non_blocking: bool, /* [!ISSUE] how to prevent data races and enforce integrity
-                             * pub optional:     bool, */
+    /// the arguments of a non-blocking symbol are asserted to be `Send` by
+    /// the macro, and receivers are locked by the caller until the call
+    /// settles
+    pub non_blocking: bool,
+    // pub optional:     bool,
+}
+
+// https://docs.deno.com/api/deno/~/Deno.UnsafeCallbackDefinition
+#[derive(Clone, Debug, PartialEq)]
+pub struct FfiCallback {
+    pub parameters: Vec<FfiType>,
+    pub result:     FfiType,
+}
+
+impl FfiCallback {
+    /// The callback definition of an `extern "C" fn` type. Parameters of types
+    /// not supported by the bindings are passed as pointers
+    pub fn new(type_bare_fn: &syn::TypeBareFn, module: &mut TsModule) -> Self {
+        let parse_type = |input: ParseStream| Type::parse(input, None);
+        let mut ffi_type = |ty: &syn::Type| match parse_type.parse2(ty.to_token_stream()) {
+            Ok(ty) => ty.unwrap(module).0,
+            Err(_) => FfiType::Pointer,
+        };
+
+        let parameters = type_bare_fn
+            .inputs
+            .iter()
+            .map(|input| ffi_type(&input.ty))
+            .collect();
+        let result = match &type_bare_fn.output {
+            syn::ReturnType::Default => FfiType::Void,
+            syn::ReturnType::Type(_, ty) => ffi_type(ty),
+        };
+        Self { parameters, result }
+    }
+
+    /// The callback definition of a closure, as seen by the javascript function
+    /// it wraps. The context pointer leading the parameters of the `extern "C"`
+    /// callback is left out
+    pub fn from_dyn_fn(dyn_fn: &TypeDynFn, module: &mut TsModule) -> Self {
+        let parameters = dyn_fn
+            .inputs
+            .iter()
+            .map(|input| input.clone().unwrap(module).0)
+            .collect();
+        let result = (*dyn_fn.output).clone().unwrap(module).0;
+        Self { parameters, result }
+    }
 }
 
 // https://docs.deno.com/api/deno/~/Deno.ForeignLibraryInterface
 #[derive(Clone, Debug, PartialEq)]
 pub enum FfiSymbol {
     Function(FfiFunction),
-    // Static(FfiStatic),
+    Static(FfiStatic),
+}
+
+// https://docs.deno.com/api/deno/~/Deno.ForeignStatic
+#[derive(Clone, Debug, PartialEq)]
+pub struct FfiStatic {
+    pub ident: Ident,
+    pub name:  String,
+    pub ty:    FfiType,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -50,9 +170,55 @@ pub struct FfiInterface {
 }
 
 impl FfiInterface {
+    /// The crate name the utility symbols are exported under
+    pub const UTILS_CRATE: &'static str = "deno_bindgen2_utils";
+
     pub fn push_fn(&mut self, ffi_function: FfiFunction) {
+        self.check_ident(&ffi_function.ident, &ffi_function.name);
         self.symbols.push(FfiSymbol::Function(ffi_function));
     }
+    pub fn push_static(&mut self, ffi_static: FfiStatic) {
+        self.check_ident(&ffi_static.ident, &ffi_static.name);
+        self.symbols.push(FfiSymbol::Static(ffi_static));
+    }
+    /// Rejects a symbol whose key is taken by another symbol. Keys join the
+    /// module path of a shim with `__`, so distinct items may share one, i.e.
+    /// `a::open` and a root `a__open`
+    fn check_ident(&self, ident: &Ident, name: &str) {
+        for symbol in &self.symbols {
+            let (other_ident, other_name) = match symbol {
+                FfiSymbol::Function(ffi_function) => (&ffi_function.ident, &ffi_function.name),
+                FfiSymbol::Static(ffi_static) => (&ffi_static.ident, &ffi_static.name),
+            };
+            if other_ident == ident && other_name != name {
+                panic!(
+                    "`{other_name}` and `{name}` are both exported as the symbol `{ident}`. \
+                     rename one of the items"
+                );
+            }
+        }
+    }
+    pub fn contains(&self, ident: &Ident) -> bool {
+        self.symbols.iter().any(|symbol| match symbol {
+            FfiSymbol::Function(ffi_function) => ffi_function.ident == *ident,
+            FfiSymbol::Static(ffi_static) => ffi_static.ident == *ident,
+        })
+    }
+    /// Declares a utility symbol from `deno-bindgen2-utils` which is linked
+    /// into the user's library, if it was not declared yet
+    pub fn push_util_fn(&mut self, ident: &str, inputs: Vec<FfiType>, output: FfiType) {
+        let name = format!("{}::{ident}", Self::UTILS_CRATE);
+        let ident = format_ident!("{ident}");
+        if !self.contains(&ident) {
+            self.push_fn(FfiFunction {
+                ident,
+                name,
+                inputs,
+                output,
+                non_blocking: false,
+            });
+        }
+    }
     pub fn append(&mut self, other: &mut FfiInterface) {
         self.symbols.append(&mut other.symbols);
     }
@@ -99,15 +265,24 @@ impl Parse for FfiType {
 impl Parse for FfiFunction {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         // ident: {}
-        let ident = input.parse()?;
+        let ident: Ident = input.parse()?;
         input.parse::<Token![:]>()?;
 
         // {...}
         let content;
         braced!(content in input);
 
+        // name: "...", defaults to a utility symbol
+        let mut name = format!("{}::{ident}", FfiInterface::UTILS_CRATE);
+        let mut _ident = content.call(<Ident as syn::ext::IdentExt>::parse_any)?;
+        if _ident.to_string().as_str() == "name" {
+            content.parse::<Token![:]>()?;
+            name = content.parse::<LitStr>()?.value();
+            content.parse::<Token![,]>()?;
+            _ident = content.call(<Ident as syn::ext::IdentExt>::parse_any)?;
+        }
+
         // parameters: []...
-        let _ident = content.call(<Ident as syn::ext::IdentExt>::parse_any)?;
         if _ident.to_string().as_str() != "parameters" {
             return Err(syn::Error::new(_ident.span(), "expected key `parameters`"));
         }
@@ -137,6 +312,7 @@ impl Parse for FfiFunction {
 
         Ok(Self {
             ident,
+            name,
             inputs,
             output,
             non_blocking: false,
@@ -185,6 +361,7 @@ impl ToTokens for FfiType {
             FfiType::Pointer   => quote! { "pointer" },
             FfiType::Buffer    => quote! { "buffer" },
             FfiType::FnPointer => quote! { "function" },
+            FfiType::Struct(fields) => quote! { { struct: [#(#fields),*] } },
         });
     }
 }
@@ -193,6 +370,7 @@ impl ToTokens for FfiFunction {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let FfiFunction {
             ident,
+            name,
             inputs,
             output,
             non_blocking,
@@ -218,6 +396,7 @@ impl ToTokens for FfiFunction {
 
         tokens.extend(quote! {
             #ident: {
+                name: #name,
                 parameters: [#inputs],
                 result: #output,
                 #non_blocking
@@ -226,10 +405,29 @@ impl ToTokens for FfiFunction {
     }
 }
 
+impl ToTokens for FfiStatic {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let FfiStatic { ident, name, ty } = self;
+        tokens.extend(quote! {
+            #ident: { name: #name, type: #ty }
+        });
+    }
+}
+
+impl ToTokens for FfiCallback {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let FfiCallback { parameters, result } = self;
+        tokens.extend(quote! {
+            { parameters: [#(#parameters),*], result: #result }
+        });
+    }
+}
+
 impl ToTokens for FfiSymbol {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         tokens.extend(match self {
             FfiSymbol::Function(ffi_function) => ffi_function.to_token_stream(),
+            FfiSymbol::Static(ffi_static) => ffi_static.to_token_stream(),
         });
     }
 }
@@ -289,10 +487,22 @@ mod tests {
     fn test_print() {
         let ffi_function = FfiFunction {
             ident:        format_ident!("some_symbol"),
+            name:         "deno_bindgen2_test::some_symbol".to_string(),
             inputs:       vec![FfiType::Pointer, FfiType::U8],
             output:       FfiType::Void,
             non_blocking: false,
         };
         println!("{}", ffi_function.to_token_stream().to_string());
     }
+
+    #[test]
+    fn test_struct_layout() {
+        let point = FfiType::Struct(vec![FfiType::F64, FfiType::F64]);
+        assert_eq!(point.layout(), (16, 8));
+
+        let (offsets, size, align) =
+            FfiType::struct_layout(&[FfiType::U8, FfiType::U32, point, FfiType::Bool]);
+        assert_eq!(offsets, [0, 4, 8, 24]);
+        assert_eq!((size, align), (32, 8));
+    }
 }
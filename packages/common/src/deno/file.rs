@@ -1,13 +1,20 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use crate::deno::util::*;
-use crate::deno::{ClassDefs, FfiLib, FunctionDefs, RustTypeDefs, TsFormat, UserDefinedDefs};
-use crate::rust::{File, Item, ItemMod};
+use crate::deno::{
+    ClassDefs, ConstDefs, EnumDefs, ErrorDefs, FfiLib, FunctionDefs, InternDefs, RustTypeDefs,
+    StructDefs, TsFormat, UserDefinedDefs,
+};
+use crate::rust::{Attribute, EnumTable, File, Item, ItemMod, Marker, StructTable};
 
 /* -------------------------------------------------------------------------- */
 
 #[derive(Clone, Debug)]
 pub struct CodegenOpts {
+    /// Name of the crate, which prefixes the exported name of every symbol
+    pub crate_name: String,
     pub file_name:  String,
     pub dylib_path: String,
     pub lazy:       bool,
@@ -21,31 +28,232 @@ pub struct CodegenOpts {
     /// file and uses the dylib from this path for the typescript representation
     /// of the extended rust types. Incompatible with `inline=true`
     pub embedded:   Option<PathBuf>,
+    /// Accept and return javascript strings for the `&str` and `String` values
+    /// of every function, as if they all had the `js_string` marker
+    pub js_string:  bool,
+    /// Print the items of rust modules in typescript namespaces of the same
+    /// name. Types are still resolved by name across the whole crate, so
+    /// their names must be unique
+    pub namespaces: bool,
+    /// Naming convention of the functions, methods, fields and constants in
+    /// typescript. Names given by a `rename` marker are left as they are
+    pub rename_all: Option<RenameRule>,
+}
+
+/// A naming convention for the `rename_all` option
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenameRule {
+    CamelCase,  // `camelCase`
+    PascalCase, // `PascalCase`
+    SnakeCase,  // `snake_case`
+}
+
+impl RenameRule {
+    /// Converts a rust name into this convention. Leading underscores are kept,
+    /// so that the fields of tuple structs stay `_0`, `_1`, ...
+    pub fn apply(&self, name: &str) -> String {
+        let trimmed = name.trim_start_matches('_');
+        let mut renamed = name[..name.len() - trimmed.len()].to_string();
+
+        // words are separated by underscores and by lowercase to uppercase
+        // transitions
+        let mut words = vec![String::new()];
+        let mut lower = false;
+        for c in trimmed.chars() {
+            if c == '_' || (lower && c.is_uppercase()) {
+                words.push(String::new());
+            }
+            if c != '_' {
+                words.last_mut().unwrap().extend(c.to_lowercase());
+            }
+            lower = c.is_lowercase() || c.is_numeric();
+        }
+        words.retain(|word| !word.is_empty());
+
+        for (i, word) in words.iter().enumerate() {
+            match self {
+                Self::SnakeCase if i > 0 => renamed.push_str(&format!("_{word}")),
+                Self::SnakeCase => renamed.push_str(word),
+                Self::CamelCase if i == 0 => renamed.push_str(word),
+                Self::CamelCase | Self::PascalCase => {
+                    let mut chars = word.chars();
+                    renamed.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+                    renamed.extend(chars);
+                },
+            }
+        }
+        renamed
+    }
+}
+
+impl FromStr for RenameRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "camelCase" => Ok(Self::CamelCase),
+            "PascalCase" => Ok(Self::PascalCase),
+            "snake_case" => Ok(Self::SnakeCase),
+            _ => Err(format!(
+                "unknown naming convention `{value}`. expected `camelCase`, `PascalCase` or \
+                 `snake_case`"
+            )),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct TsModule {
-    pub ffi_lib:    FfiLib, // symbol definitions
-    pub type_defs:  RustTypeDefs, /* import statements or type definitions if `inline = true`.
-                             * links to standard types */
-    pub user_defs:  UserDefinedDefs,
-    pub functions:  FunctionDefs,
-    pub class_defs: ClassDefs,
+    pub ffi_lib:     FfiLib, // symbol definitions
+    pub type_defs:   RustTypeDefs, /* import statements or type definitions if `inline = true`.
+                              * links to standard types */
+    pub user_defs:   UserDefinedDefs,
+    pub error_defs:  ErrorDefs,
+    pub functions:   FunctionDefs,
+    pub class_defs:  ClassDefs,
+    pub intern_defs: InternDefs,
+    pub struct_defs: StructDefs,
+    pub enum_defs:   EnumDefs,
+    pub const_defs:  ConstDefs,
+    pub crate_name:  String,
+    /// path of the module being transformed, relative to the crate root
+    pub mod_path:    Vec<Ident>,
+    /// path of the module each struct and enum is declared in
+    pub type_paths:  BTreeMap<Ident, Vec<Ident>>,
+    /// name in typescript of each struct and enum with a `rename` marker
+    pub type_names:  BTreeMap<Ident, Ident>,
+    pub namespaces:  bool,
+    pub rename_all:  Option<RenameRule>,
 }
 
 // TODO: use buffer where possible to handle large projects
 
 impl TsModule {
-    pub fn new(file: File, opts: &CodegenOpts) -> Self {
+    pub fn new(mut file: File, opts: &CodegenOpts) -> Self {
         let mut module = TsModule::default();
         module.ffi_lib.dylib_path = opts.dylib_path.clone();
         module.ffi_lib.lazy = opts.lazy;
+        module.crate_name = opts.crate_name.clone();
+        module.namespaces = opts.namespaces;
+        module.rename_all = opts.rename_all;
+
+        if opts.js_string {
+            for item in &mut file.items {
+                item.push_marker(&Marker::JsString);
+            }
+        }
+
+        // resolved the same way as the macro does, from the structs of this file
+        let structs = StructTable::collect(&file.items);
+        let enums = EnumTable::collect(&file.items);
+        for item in &mut file.items {
+            item.resolve_types(&structs, &enums)
+                .expect("failed to resolve the structs passed by value and the enums");
+        }
+        module.struct_defs.collect(&file.items, &structs);
+        module.collect_type_paths(&file.items);
 
         module.unwrap(file.items);
-        module.user_defs.dedup(&module.class_defs);
+        // classes are declared by their name in typescript
+        let classes = module.class_defs.store.values().map(|class| &class.path);
+        module.user_defs.dedup(classes);
+        let structs = module.struct_defs.store.values().map(|ts| &ts.path);
+        module.user_defs.dedup(structs);
+        module.user_defs.dedup(module.enum_defs.store.keys());
         module
     }
 
+    /// Records the module path of the structs and enums that emit a drop shim,
+    /// along with the name of those that are renamed. Types are referred to by
+    /// their name alone, so a name may only be declared in one module
+    fn collect_type_paths(&mut self, items: &[Item]) {
+        for item in items {
+            let (ident, attr) = match item {
+                Item::Struct(item_struct) => (&item_struct.ident, &item_struct.attr),
+                Item::Enum(item_enum) => (&item_enum.ident, &item_enum.attr),
+                Item::Mod(item_mod) => {
+                    self.mod_path.push(item_mod.ident.clone());
+                    self.collect_type_paths(&item_mod.items);
+                    self.mod_path.pop();
+                    continue;
+                },
+                _ => continue,
+            };
+            if let Some(mod_path) = self.type_paths.get(ident) {
+                if *mod_path != self.mod_path {
+                    let path = |mod_path: &[Ident]| {
+                        let mut path = String::from("crate");
+                        for ident in mod_path {
+                            path.push_str(&format!("::{ident}"));
+                        }
+                        path
+                    };
+                    panic!(
+                        "`{ident}` is declared in both `{}` and `{}`. types are referred to by \
+                         their name alone, so rename one of them",
+                        path(mod_path),
+                        path(&self.mod_path),
+                    );
+                }
+            }
+            self.type_paths.insert(ident.clone(), self.mod_path.clone());
+            if let Some(name) = attr.rename() {
+                self.type_names
+                    .insert(ident.clone(), format_ident!("{name}"));
+            }
+        }
+    }
+
+    /// The name of a struct or enum in typescript
+    pub fn type_name(&self, ident: &Ident) -> Ident {
+        self.type_names.get(ident).unwrap_or(ident).clone()
+    }
+
+    /// The name of a struct or enum as it is referred to in typescript, within
+    /// the namespace of its module
+    pub fn type_path(&self, ident: &Ident) -> TsPath {
+        let namespace = match self.type_paths.get(ident) {
+            Some(path) if self.namespaces => path.clone(),
+            _ => Vec::new(),
+        };
+        TsPath {
+            namespace,
+            ident: self.type_name(ident),
+        }
+    }
+
+    /// The name of a function, method, field or constant in typescript. A
+    /// `rename` marker takes precedence over the `rename_all` convention
+    pub fn ts_name(&self, attr: &Attribute, ident: &Ident) -> Ident {
+        match (attr.rename(), self.rename_all) {
+            (Some(name), _) => format_ident!("{name}"),
+            (None, Some(rule)) => format_ident!("{}", rule.apply(&ident.to_string())),
+            (None, None) => ident.clone(),
+        }
+    }
+
+    /// The key of a symbol whose shim is expanded in the given module, along
+    /// with the name it is exported under. Shims outside the crate root are
+    /// keyed by their module path as well, so that they stay unique
+    pub fn symbol(&self, mod_path: &[Ident], shim: &Ident) -> (Ident, String) {
+        let mut key = String::new();
+        let mut name = self.crate_name.clone();
+        for ident in mod_path {
+            key.push_str(&format!("__{ident}"));
+            name.push_str(&format!("::{ident}"));
+        }
+        (format_ident!("{key}{shim}"), format!("{name}::{shim}"))
+    }
+
+    /// The typescript namespace of the module being transformed
+    pub fn namespace(&self) -> Vec<Ident> {
+        if self.namespaces {
+            self.mod_path.clone()
+        } else {
+            Vec::new()
+        }
+    }
+
     /// Recursively transforms parsed rust items into their typescript
     /// representations
     fn unwrap(&mut self, items: Vec<Item>) {
@@ -53,15 +261,26 @@ impl TsModule {
             match item {
                 Item::Fn(item_fn) => {
                     let method = item_fn.unwrap(self);
-                    self.functions.push(method);
+                    let namespace = self.namespace();
+                    self.functions.namespace(&namespace).push(method);
                 },
                 Item::Impl(item_impl) => {
                     item_impl.unwrap(self);
                 },
-                Item::Mod(ItemMod { items, .. }) => {
+                Item::Mod(ItemMod { ident, items, .. }) => {
+                    self.mod_path.push(ident);
                     self.unwrap(items);
+                    self.mod_path.pop();
+                },
+                Item::Struct(item_struct) => {
+                    item_struct.unwrap(self);
+                },
+                Item::Enum(item_enum) => {
+                    item_enum.unwrap(self);
+                },
+                Item::Static(item_static) => {
+                    item_static.unwrap(self);
                 },
-                _ => (),
             }
         }
     }
@@ -72,8 +291,14 @@ impl TsModule {
             mut ffi_lib,
             mut type_defs,
             user_defs,
+            error_defs,
             functions,
             class_defs,
+            intern_defs,
+            struct_defs,
+            enum_defs,
+            const_defs,
+            ..
         } = self;
 
         type_defs.extended = opts.extended;
@@ -82,15 +307,25 @@ impl TsModule {
 
         let ffi_lib = ffi_lib.to_token_stream().to_string();
         let user_defs = user_defs.to_token_stream().to_string();
+        let error_defs = error_defs.to_token_stream().to_string();
         let functions = functions.to_token_stream().to_string();
+        let struct_defs = struct_defs.to_token_stream().to_string();
+        let enum_defs = enum_defs.to_token_stream().to_string();
         let class_defs = class_defs.to_token_stream().to_string();
+        let intern_defs = intern_defs.to_token_stream().to_string();
+        let const_defs = const_defs.to_token_stream().to_string();
 
         TsFormat::format(format!(
             "// deno-lint-ignore-file\n
             {ffi_lib}
             {type_defs}
             {user_defs}
+            {error_defs}
+            {enum_defs}
+            {struct_defs}
             {class_defs}
+            {intern_defs}
+            {const_defs}
             {functions}
             "
         ))
@@ -106,8 +341,13 @@ impl TsModule {
         let TsModule {
             mut ffi_lib,
             user_defs,
+            error_defs,
             functions,
             class_defs,
+            intern_defs,
+            struct_defs,
+            enum_defs,
+            const_defs,
             ..
         } = self;
 
@@ -159,15 +399,25 @@ impl TsModule {
 
         let ffi_lib = ffi_lib.to_token_stream().to_string();
         let user_defs = user_defs.to_token_stream().to_string();
+        let error_defs = error_defs.to_token_stream().to_string();
         let functions = functions.to_token_stream().to_string();
+        let struct_defs = struct_defs.to_token_stream().to_string();
+        let enum_defs = enum_defs.to_token_stream().to_string();
         let class_defs = class_defs.to_token_stream().to_string();
+        let intern_defs = intern_defs.to_token_stream().to_string();
+        let const_defs = const_defs.to_token_stream().to_string();
 
         let module = TsFormat::format(format!(
             "
             {imports}
             {ffi_lib}
             {user_defs}
+            {error_defs}
+            {enum_defs}
+            {struct_defs}
             {class_defs}
+            {intern_defs}
+            {const_defs}
             {functions}
             "
         ));
@@ -175,3 +425,312 @@ impl TsModule {
         (module, type_defs)
     }
 }
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(source: TokenStream, namespaces: bool, rename_all: Option<RenameRule>) -> TsModule {
+        let file = File::parse_str(source.to_string().as_str());
+        TsModule::new(file, &CodegenOpts {
+            crate_name: "deno_bindgen2_test".to_string(),
+            file_name: String::new(),
+            dylib_path: String::new(),
+            lazy: false,
+            extended: false,
+            embedded: None,
+            js_string: false,
+            namespaces,
+            rename_all,
+        })
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_mod_path() {
+        let module = module(
+            quote! {
+                #[doc = "deno_bindgen"]
+                fn open() {}
+
+                mod a {
+                    #[doc = "deno_bindgen"]
+                    fn open() {}
+
+                    mod b {
+                        #[doc = "deno_bindgen"]
+                        fn open() {}
+                    }
+                }
+            },
+            false,
+            None,
+        );
+
+        let ffi_lib = TsFormat::format(module.ffi_lib.to_token_stream().to_string());
+        assert!(ffi_lib.contains(r#"__open: { name: "deno_bindgen2_test::__open","#));
+        assert!(ffi_lib.contains(r#"__a__open: { name: "deno_bindgen2_test::a::__open","#));
+        assert!(ffi_lib.contains(r#"name: "deno_bindgen2_test::a::b::__open","#));
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains("symbols.__a__b__open(out_panic);"));
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(feature = "cli")]
+    fn test_mod_path_collision() {
+        module(
+            quote! {
+                #[doc = "deno_bindgen"]
+                fn a__open() {}
+
+                mod a {
+                    #[doc = "deno_bindgen"]
+                    fn open() {}
+                }
+            },
+            false,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "`Point` is declared in both `crate::a` and `crate::b`")]
+    #[cfg(feature = "cli")]
+    fn test_type_collision() {
+        module(
+            quote! {
+                mod a {
+                    #[doc = "deno_bindgen"]
+                    struct Point {}
+                }
+
+                mod b {
+                    #[doc = "deno_bindgen"]
+                    struct Point {}
+                }
+            },
+            false,
+            None,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_namespaces() {
+        let module = module(
+            quote! {
+                #[doc = "deno_bindgen"]
+                fn open() {}
+
+                mod a {
+                    #[doc = "deno_bindgen"]
+                    const LIMIT: u8 = 4;
+
+                    #[doc = "deno_bindgen"]
+                    fn open() {}
+
+                    mod b {
+                        #[doc = "deno_bindgen"]
+                        fn open() {}
+                    }
+                }
+            },
+            true,
+            None,
+        );
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains("export namespace a {\n    export function open() {"));
+        assert!(functions.contains("    export namespace b {\n        export function open() {"));
+        assert!(functions.contains("symbols.__a__b__open(out_panic);"));
+        assert_eq!(functions.matches("export function open() {").count(), 3);
+
+        let const_defs = TsFormat::format(module.const_defs.to_token_stream().to_string());
+        assert!(const_defs.contains("export namespace a {\n    export const LIMIT: RustU8 = 4;"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_namespaced_types() {
+        let module = module(
+            quote! {
+                #[doc = "deno_bindgen"]
+                fn area(shape: Shape, origin: Point, mode: Mode) -> f64 {}
+
+                mod geo {
+                    #[doc = "deno_bindgen"]
+                    struct Shape {}
+
+                    #[doc = "deno_bindgen"]
+                    impl Shape {
+                        fn origin(&self) -> Point {}
+                    }
+
+                    #[doc = "deno_bindgen"]
+                    impl Iterator for Shape {
+                        type Item = f64;
+                    }
+
+                    #[doc = "deno_bindgen"]
+                    #[repr(C)]
+                    struct Point { x: f64, y: f64 }
+
+                    #[doc = "deno_bindgen"]
+                    #[repr(u8)]
+                    enum Mode { Fill, Stroke }
+                }
+            },
+            true,
+            None,
+        );
+
+        let class_defs = TsFormat::format(module.class_defs.to_token_stream().to_string());
+        assert!(class_defs.starts_with(
+            "export namespace geo {\n    export class Shape extends RustPrototype<Shape> {"
+        ));
+        assert!(class_defs.contains("origin(): geo.Point {"));
+        // the drop shim is keyed by the module of the type
+        assert!(class_defs.contains("symbols.__geo__Shape__drop(this.take());"));
+
+        let ffi_lib = TsFormat::format(module.ffi_lib.to_token_stream().to_string());
+        assert!(ffi_lib.contains(r#"name: "deno_bindgen2_test::geo::__Shape__drop","#));
+        assert!(ffi_lib.contains("__geo__Shape__drop: {"));
+
+        let struct_defs = TsFormat::format(module.struct_defs.to_token_stream().to_string());
+        assert!(struct_defs.starts_with("export namespace geo {\n    export class Point {"));
+
+        let enum_defs = TsFormat::format(module.enum_defs.to_token_stream().to_string());
+        assert!(enum_defs.starts_with("export namespace geo {\n    export enum Mode {"));
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains(
+            "export function area(arg_0: geo.Shape, arg_1: geo.Point, arg_2: geo.Mode): RustF64 {"
+        ));
+        assert!(functions.contains("symbols.__area(arg_0.take(), arg_1.toBuffer(), arg_2);"));
+    }
+
+    #[test]
+    fn test_rename_rule() {
+        assert_eq!(RenameRule::CamelCase.apply("test_ref_self"), "testRefSelf");
+        assert_eq!(RenameRule::CamelCase.apply("_0"), "_0");
+        assert_eq!(RenameRule::CamelCase.apply("__private_len"), "__privateLen");
+        assert_eq!(RenameRule::PascalCase.apply("to_u8"), "ToU8");
+        assert_eq!(RenameRule::SnakeCase.apply("labelLen"), "label_len");
+        assert_eq!("camelCase".parse(), Ok(RenameRule::CamelCase));
+        assert!("kebab-case".parse::<RenameRule>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_rename() {
+        let module = module(
+            quote! {
+                #[doc = "deno_bindgen"]
+                fn test_ref_self() {}
+
+                #[doc = "deno_bindgen"]
+                #[doc = "deno_bindgen_rename:openFile"]
+                fn open() {}
+
+                #[doc = "deno_bindgen"]
+                #[doc = "deno_bindgen_rename:Stack"]
+                struct RustStack {
+                    #[doc = "deno_bindgen_get"]
+                    max_len: usize,
+                }
+
+                #[doc = "deno_bindgen"]
+                impl RustStack {
+                    fn push_value(&mut self, value: u8) {}
+
+                    #[doc = "deno_bindgen_rename:top"]
+                    fn peek_value(&self) -> &u8 {}
+
+                    fn from_parts(len: usize) -> RustStack {}
+                }
+
+                #[doc = "deno_bindgen"]
+                #[doc = "deno_bindgen_rename:MAX_LEN"]
+                const MAX_STACK_LEN: u8 = 4;
+            },
+            false,
+            Some(RenameRule::CamelCase),
+        );
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains("export function testRefSelf() {"));
+        assert!(functions.contains("symbols.__test_ref_self(out_panic);"));
+        assert!(functions.contains("export function openFile() {"));
+        assert!(functions.contains("symbols.__open(out_panic);"));
+
+        let class_defs = TsFormat::format(module.class_defs.to_token_stream().to_string());
+        assert!(class_defs.contains("export class Stack extends RustPrototype<Stack> {"));
+        assert!(class_defs.contains("get maxLen(): RustUsize {"));
+        assert!(class_defs.contains("symbols.__RustStack__get_max_len(this.ptr)"));
+        assert!(class_defs.contains("pushValue(arg_0: RustU8) {"));
+        assert!(class_defs.contains("top(): RustRef<RustU8> {"));
+        assert!(class_defs.contains("static fromParts(arg_0: RustUsize): Stack {"));
+        assert!(!class_defs.contains("RustStack extends"));
+
+        let ffi_lib = TsFormat::format(module.ffi_lib.to_token_stream().to_string());
+        assert!(ffi_lib.contains(r#"name: "deno_bindgen2_test::__RustStack__drop","#));
+
+        let const_defs = TsFormat::format(module.const_defs.to_token_stream().to_string());
+        assert!(const_defs.contains("export const MAX_LEN: RustU8 = 4;"));
+        assert!(module.user_defs.to_token_stream().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_error_classes() {
+        let module = module(
+            quote! {
+                #[doc = "deno_bindgen"]
+                fn parse(arg0: u8) -> Result<u8, ParseIntError> {}
+
+                #[doc = "deno_bindgen"]
+                fn load() -> Result<(), String> {}
+
+                #[doc = "deno_bindgen"]
+                fn save() -> Result<(), String> {}
+
+                #[doc = "deno_bindgen"]
+                struct IoError {}
+
+                #[doc = "deno_bindgen"]
+                fn open() -> Result<(), IoError> {}
+
+                #[doc = "deno_bindgen"]
+                fn close() -> Result<(), Error> {}
+            },
+            false,
+            None,
+        );
+
+        let error_defs = TsFormat::format(module.error_defs.to_token_stream().to_string());
+        assert!(error_defs.contains("export class ParseIntError extends RustError {"));
+        assert!(error_defs.contains(r#"this.name = "ParseIntError";"#));
+        assert_eq!(
+            error_defs
+                .matches("export class StringError extends RustError {")
+                .count(),
+            1
+        );
+        // the exported `IoError` class keeps its name
+        assert!(error_defs.contains("export class IoErrorError extends RustError {"));
+        // a bare `Error` would shadow the global one
+        assert!(error_defs.contains("export class ErrorError extends RustError {"));
+        assert!(!error_defs.contains("export class Error extends RustError {"));
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains("throw new ParseIntError(message);"));
+        assert!(functions.contains("throw new IoErrorError(message);"));
+        assert!(functions.contains("@throws {StringError} the `String` of an `Err`"));
+    }
+}
@@ -1,21 +1,29 @@
+use std::collections::BTreeMap;
+
 use crate::deno::util::*;
-use crate::deno::{FfiFunction, RustType, TsModule};
-use crate::rust::{Association, Attribute, ItemFn};
+use crate::deno::{ErrorDefs, FfiCallback, FfiFunction, FfiType, RustType, RustTypeList, TsModule};
+use crate::rust::{Association, Attribute, ItemFn, Type};
 
 #[derive(Clone, Debug, Default)]
 pub struct FunctionDefs {
-    pub functions: Vec<TsMethod>,
+    pub functions:  Vec<TsMethod>,
+    /// functions of the rust modules, printed as typescript namespaces
+    pub namespaces: BTreeMap<Ident, FunctionDefs>,
 }
 
 #[derive(Clone, Debug)]
 pub struct TsMethod {
     pub attr:      Attribute,
+    pub doc:       JsDoc,
     pub ident:     Ident,
     pub inputs:    Vec<RustType>,
     pub output:    RustType,
     pub assoc:     Option<Association>,
     pub ffi_ident: Ident,
     pub self_ty:   Option<Ident>,
+    /// whether the shim spawns a task, whose result is taken by the
+    /// `__await` shim once it is done
+    pub async_:    bool,
 }
 
 /* -------------------------------------------------------------------------- */
@@ -24,28 +32,157 @@ pub struct TsMethod {
 
 impl ItemFn {
     pub fn unwrap(self, module: &mut TsModule) -> TsMethod {
+        // accessors of interned types are exported at the crate root
+        let mod_path = if self.block.interned {
+            Vec::new()
+        } else {
+            module.mod_path.clone()
+        };
+        let (ffi_ident, ffi_name) = module.symbol(&mod_path, &self.shim_ident());
+        let (await_ident, await_name) = module.symbol(&mod_path, &self.await_ident());
+        let js_string = self.attr.has_js_string();
+        let doc = self.jsdoc(module);
+
         // transform types
 
+        let cancellable = self.attr.has_cancellable();
         let mut ffi_inputs = Vec::new();
         let mut inputs = Vec::new();
         for input in self.inputs {
+            // the token of a cancellable function is a buffer owned by the
+            // caller, tripped when its `AbortSignal` is aborted
+            if cancellable && input.is_cancellation_token() {
+                module.ffi_lib.interface.push_util_fn(
+                    "__CancellationToken__cancel",
+                    vec![FfiType::Buffer],
+                    FfiType::Void,
+                );
+                ffi_inputs.push(FfiType::Buffer);
+                continue;
+            }
+            // a `Result<T, E>` is only meaningful as a result type
+            let input = match input {
+                Type::Result(..) => Type::Unsupported(syn::parse_quote!(#input)),
+                // the shim borrows its own copy of a struct passed by value
+                Type::Ref(elem) if matches!(*elem, Type::Struct(_)) => *elem,
+                input => input,
+            };
+            // closures are passed as a callback and its context pointer. boxed
+            // closures also take the function releasing their callback
+            if let Some(dyn_fn) = input.closure() {
+                let boxed = matches!(input, Type::Box(_));
+                ffi_inputs.push(FfiType::FnPointer);
+                ffi_inputs.push(FfiType::Pointer);
+                if boxed {
+                    module.type_defs.insert(RustTypeList::Callback);
+                    ffi_inputs.push(FfiType::FnPointer);
+                }
+                inputs.push(RustType::Closure(
+                    FfiCallback::from_dyn_fn(dyn_fn, module),
+                    boxed,
+                ));
+                continue;
+            }
+            // numeric slices are passed as a typed array and its length
+            if let Some(elem) = input.buffer_slice_elem() {
+                let (_, elem) = elem.clone().unwrap(module);
+                ffi_inputs.push(FfiType::Buffer);
+                ffi_inputs.push(FfiType::Usize);
+                inputs.push(RustType::TypedArray(Box::new(elem)));
+                continue;
+            }
+            // strings are encoded into a utf-8 buffer, passed with its length
+            if js_string && is_string(&input) {
+                ffi_inputs.push(FfiType::Buffer);
+                ffi_inputs.push(FfiType::Usize);
+                inputs.push(RustType::JsString);
+                continue;
+            }
             let (ffi_input, input) = input.unwrap(module);
             ffi_inputs.push(ffi_input);
             inputs.push(input);
         }
 
-        let (ffi_output, output) = self.output.unwrap(module);
+        // the shim of an `async` function takes a callback notifying that its
+        // task is done. the result is returned by the `__await` shim instead
+        if self.async_.is_some() {
+            ffi_inputs.push(FfiType::FnPointer);
+            module.ffi_lib.interface.push_fn(FfiFunction {
+                ident:        ffi_ident.clone(),
+                name:         ffi_name.clone(),
+                inputs:       ffi_inputs,
+                output:       FfiType::Pointer,
+                non_blocking: false,
+            });
+            ffi_inputs = vec![FfiType::Pointer];
+        }
 
-        let ident = self.ident;
-        let ffi_ident = if let Some(self_ty) = &self.block.self_ty {
-            format_ident!("__{}__{ident}", self_ty.to_string())
+        if js_string && self.output == Type::String {
+            ffi_inputs.push(FfiType::Buffer);
         } else {
-            format_ident!("__{ident}")
+            push_out_params(&self.output, &mut ffi_inputs);
+        }
+        // the message of a panic in a non-blocking call is written to a
+        // trailing buffer, as the call runs on another thread. so is the one of
+        // a call without a result, which has no sentinel to return
+        let panic_slot = self.attr.has_non_blocking() || self.output == Type::Void;
+        if panic_slot {
+            ffi_inputs.push(FfiType::Buffer);
+        }
+
+        // tagged `Option<T>` results return a tag and write their value to a
+        // trailing buffer parameter
+        let (ffi_output, output) = match self.output {
+            // decoded and freed by the caller
+            Type::String if js_string => {
+                module.ffi_lib.interface.push_util_fn(
+                    "__JsString__drop",
+                    vec![FfiType::Pointer, FfiType::Usize],
+                    FfiType::Void,
+                );
+                (FfiType::Pointer, RustType::JsString)
+            },
+            Type::Option(elem) if elem.is_buffer_elem() => {
+                (FfiType::Bool, Type::Option(elem).unwrap(module).1)
+            },
+            output => output.unwrap(module),
+        };
+
+        // a `set_name` setter assigns the `name` property
+        let ident = match self.ident.to_string().strip_prefix("set_") {
+            Some(name) if self.attr.has_setter() => format_ident!("{name}"),
+            _ => self.ident,
         };
+        // the accessors of interned types keep the names used by the utils
+        let ident = if self.block.interned {
+            ident
+        } else {
+            module.ts_name(&self.attr, &ident)
+        };
+
+        module.type_defs.insert(RustTypeList::Panic);
+        if !panic_slot || self.async_.is_some() {
+            module.ffi_lib.interface.push_util_fn(
+                "__RustPanic__take_message",
+                vec![],
+                FfiType::Pointer,
+            );
+        }
+        module.ffi_lib.interface.push_util_fn(
+            "__RustPanic__drop",
+            vec![FfiType::Pointer],
+            FfiType::Void,
+        );
 
         // code generation
+        let (symbol_ident, symbol_name) = if self.async_.is_some() {
+            (await_ident, await_name)
+        } else {
+            (ffi_ident.clone(), ffi_name)
+        };
         module.ffi_lib.interface.push_fn(FfiFunction {
-            ident:        ffi_ident.clone(),
+            ident:        symbol_ident,
+            name:         symbol_name,
             inputs:       ffi_inputs,
             output:       ffi_output,
             non_blocking: self.attr.has_non_blocking(),
@@ -53,20 +190,107 @@ impl ItemFn {
 
         TsMethod {
             attr: self.attr,
+            doc,
             ident,
             inputs,
             output,
             assoc: self.assoc.clone(),
             ffi_ident,
             self_ty: self.block.self_ty,
+            async_: self.async_.is_some(),
         }
     }
 }
 
+impl ItemFn {
+    /// The JSDoc of the function, with the tags of its parameters and result
+    /// synthesised from the rust signature
+    fn jsdoc(&self, module: &TsModule) -> JsDoc {
+        let mut doc = JsDoc::new(&self.attr);
+        // accessors are documented by their field alone
+        if self.attr.has_get() || self.attr.has_set() {
+            return doc;
+        }
+
+        // the receiver is bound to `this`, and the parameters are numbered
+        // as in `TsMethod::print`
+        let receiver = !matches!(self.assoc, None | Some(Association::Static));
+        let params = self
+            .inputs
+            .iter()
+            .zip(&self.block.params)
+            .skip(receiver as usize)
+            .filter(|(input, _)| **input != Type::Void && !input.is_cancellation_token());
+        for (i, (input, name)) in params.enumerate() {
+            doc.tag(
+                "param",
+                &format!("arg_{i} - `{name}: {}`", rust_text(input)),
+            );
+        }
+        if self.attr.has_cancellable() {
+            doc.tag(
+                "param",
+                "signal - aborts the call, tripping its cancellation token",
+            );
+        }
+
+        let (output, error) = match &self.output {
+            Type::Result(ok, err) => (&**ok, Some(err)),
+            output => (output, None),
+        };
+        if *output != Type::Void && !self.attr.has_constructor() {
+            doc.tag("returns", &format!("`{}`", rust_text(output)));
+        }
+        if let Some(err) = error {
+            let class = ErrorDefs::name(err, module);
+            doc.tag(
+                "throws",
+                &format!("{{{class}}} the `{}` of an `Err`", rust_text(err)),
+            );
+        }
+        doc
+    }
+}
+
+/// Whether this type is passed as a javascript string by the `js_string` marker
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::String => true,
+        Type::Ref(elem) => **elem == Type::Str,
+        _ => false,
+    }
+}
+
+/// Appends the ffi types of the trailing out-parameters of a shim's result
+fn push_out_params(output: &Type, ffi_inputs: &mut Vec<FfiType>) {
+    match output {
+        Type::Option(elem) if elem.is_buffer_elem() => ffi_inputs.push(FfiType::Buffer),
+        Type::Result(ok, _) => {
+            push_out_params(ok, ffi_inputs);
+            if **ok != Type::Void {
+                ffi_inputs.push(FfiType::Buffer);
+            }
+        },
+        _ => (),
+    }
+}
+
 impl FunctionDefs {
     pub fn push(&mut self, method: TsMethod) {
         self.functions.push(method);
     }
+
+    /// The functions of a nested namespace, declaring it if needed
+    pub fn namespace(&mut self, path: &[Ident]) -> &mut Self {
+        match path.split_first() {
+            Some((ident, rest)) => self
+                .namespaces
+                .entry(ident.clone())
+                .or_default()
+                .namespace(rest),
+            None => self,
+        }
+    }
 }
 
 /* -------------------------------------------------------------------------- */
@@ -93,6 +317,10 @@ impl TsMethod {
 
         let mut fn_args = Vec::new();
         let mut call_args = Vec::new();
+        let mut stmts = TokenStream::new();
+        // callbacks wrapping javascript functions, closed once the call returns
+        let mut callbacks = Vec::new();
+        let mut setup = TokenStream::new();
 
         if !inputs.is_empty() {
             let inputs_slice = if let Some(assoc) = assoc {
@@ -110,69 +338,203 @@ impl TsMethod {
 
             for (i, input) in inputs_slice.iter().enumerate() {
                 let fn_arg = format_ident!("arg_{i}");
+
+                let wrapper = format_ident!("{fn_arg}_callback");
+                // the task of an `async` function may call it from a worker
+                let constructor = if self.attr.has_non_blocking() || self.async_ {
+                    quote! { Deno.UnsafeCallback.threadSafe }
+                } else {
+                    quote! { new Deno.UnsafeCallback }
+                };
+
+                // closures wrap a javascript function, which ignores the
+                // context pointer passed back by rust
+                if let RustType::Closure(callback, boxed) = input {
+                    let FfiCallback { parameters, result } = callback;
+                    let values: Vec<Ident> = (0..parameters.len())
+                        .map(|i| format_ident!("value_{i}"))
+                        .collect();
+                    let definition = quote! {
+                        { parameters: ["pointer", #(#parameters),*], result: #result } as const
+                    };
+                    let function = quote! { (_ctx, #(#values),*) => #fn_arg(#(#values),*) };
+                    fn_args.push(quote! { #fn_arg: #input });
+                    if *boxed {
+                        // referenced until rust drops the closure, which may
+                        // call it from any thread
+                        setup.extend(quote! {
+                            const #wrapper = Deno.UnsafeCallback.threadSafe(#definition, #function);
+                        });
+                        call_args.push(quote! {
+                            #wrapper.pointer,
+                            RustCallbacks.register(#fn_arg, #wrapper),
+                            RustCallbacks.release
+                        });
+                    } else {
+                        setup.extend(quote! {
+                            const #wrapper = #constructor(#definition, #function);
+                        });
+                        call_args.push(quote! { #wrapper.pointer, null });
+                        callbacks.push(wrapper);
+                    }
+                    continue;
+                }
+
+                // function pointers also accept a plain javascript function
+                if let RustType::FnPtr(_, callback) = input {
+                    let FfiCallback { parameters, result } = callback;
+                    fn_args.push(quote! {
+                        #fn_arg: Deno.UnsafeCallbackFunction<[#(#parameters),*], #result> | #input
+                    });
+                    setup.extend(quote! {
+                        const #wrapper = typeof #fn_arg == "function"
+                            ? #constructor(#callback as const, #fn_arg)
+                            : null;
+                    });
+                    call_args.push(quote! { #wrapper ? #wrapper.pointer : #fn_arg });
+                    callbacks.push(wrapper);
+                    continue;
+                }
+
                 fn_args.push(quote! { #fn_arg: #input });
 
                 match input {
                     RustType::Void => (),
+                    RustType::Option(elem) => {
+                        call_args.push(if let Some(array) = elem.typed_array() {
+                            let value = match &**elem {
+                                RustType::Boolean => quote! { Number(#fn_arg) },
+                                RustType::Char => quote! { #fn_arg.get() },
+                                _ => quote! { #fn_arg },
+                            };
+                            quote! { #fn_arg == null ? null : new #array([#value]) }
+                        } else if elem.is_prototype() {
+                            quote! { #fn_arg == null ? null : #fn_arg.take() }
+                        } else {
+                            quote! { #fn_arg }
+                        })
+                    },
                     rest => call_args.push(match rest {
                         RustType::Numeric(_)
                         | RustType::Boolean
-                        | RustType::FnPtr(_)
+                        | RustType::FnPtr(..)
                         | RustType::Ptr(_)
                         | RustType::PtrMut(_)
                         | RustType::Ref(_)
                         | RustType::RefMut(_)
+                        | RustType::Enum(_)
                         | RustType::Unsupported => quote! { #fn_arg },
                         RustType::Char => quote! { #fn_arg.get() },
+                        RustType::TypedArray(_) => quote! { #fn_arg, #fn_arg.length },
+                        RustType::Struct(_) => quote! { #fn_arg.toBuffer() },
+                        RustType::JsString => {
+                            let buf = format_ident!("{fn_arg}_buf");
+                            stmts.extend(quote! {
+                                const #buf = new TextEncoder().encode(#fn_arg);
+                            });
+                            quote! { #buf, #buf.length }
+                        },
                         _ => quote! { #fn_arg.take() },
                     }),
                 };
             }
         }
 
-        let mut stmts = TokenStream::new();
+        // the token is read by rust until the call returns, so the listener
+        // tripping it is removed once the call settles
+        let mut cleanup = TokenStream::new();
+        if self.attr.has_cancellable() {
+            fn_args.push(quote! { signal?: AbortSignal });
+            setup = quote! {
+                const token = new Uint8Array(1);
+                const cancel = () => symbols.__CancellationToken__cancel(token);
+                if (signal) {
+                    signal.throwIfAborted();
+                    signal.addEventListener("abort", cancel, { once: true });
+                }
+                #setup
+            };
+            call_args.push(quote! { token });
+            cleanup.extend(quote! {
+                if (signal) signal.removeEventListener("abort", cancel);
+            });
+        }
+
+        let mut await_args = Vec::new();
+        if self.async_ {
+            out_buffers(output, &mut stmts, &mut await_args);
+        } else {
+            out_buffers(output, &mut stmts, &mut call_args);
+        }
+        let non_blocking = self.attr.has_non_blocking();
+        let panic_slot = non_blocking || *output == RustType::Void;
+        if panic_slot {
+            stmts.extend(quote! { const out_panic = new BigUint64Array(1); });
+            if self.async_ {
+                await_args.push(quote! { out_panic });
+            } else {
+                call_args.push(quote! { out_panic });
+            }
+        }
 
         let mut ident = quote! { #ident };
+        // free functions are declared `async` by `FunctionDefs`
+        if assoc.is_some() && (self.async_ || non_blocking) {
+            ident = quote! { async #ident };
+        }
 
+        // the raw result of the call, which an `Err` is returned as
+        let out = match output {
+            RustType::Result(..) => quote! { err },
+            _ => quote! { out },
+        };
         let fn_output;
         let call_expr = match output {
+            // spawns the task, notifying its completion through a callback
+            _ if self.async_ => {
+                setup.extend(quote! {
+                    const task_done = Promise.withResolvers<void>();
+                    const task_callback = Deno.UnsafeCallback.threadSafe(
+                        { parameters: [], result: "void" } as const,
+                        () => task_done.resolve(),
+                    );
+                });
+                callbacks.push(format_ident!("task_callback"));
+                call_args.push(quote! { task_callback.pointer });
+                fn_output = quote! { : Promise<#output> };
+                quote! { const task = symbols.#ffi_ident }
+            },
+            RustType::Void if non_blocking => {
+                fn_output = quote! { : Promise<void> };
+                quote! { await symbols.#ffi_ident }
+            },
             RustType::Void => {
                 fn_output = TokenStream::new();
                 quote! { symbols.#ffi_ident }
             },
             rest => {
-                if self.attr.has_non_blocking() {
-                    ident = quote! { async #ident };
+                if non_blocking {
                     fn_output = quote! { : Promise<#rest> };
-                    quote! { const out = await symbols.#ffi_ident }
+                    quote! { const #out = await symbols.#ffi_ident }
                 } else {
                     fn_output = quote! { : #rest };
-                    quote! { const out = symbols.#ffi_ident }
+                    quote! { const #out = symbols.#ffi_ident }
                 }
             },
         };
 
-        let generate_return_stmt = || -> TokenStream {
-            match &output {
-                RustType::Void => TokenStream::new(),
-                RustType::Numeric(_)
-                | RustType::Boolean
-                | RustType::FnPtr(_)
-                | RustType::Ptr(_)
-                | RustType::PtrMut(_)
-                | RustType::Ref(_)
-                | RustType::RefMut(_)
-                | RustType::Unsupported => quote! {
-                    return out! as #output;
-                },
-                rest => quote! {
-                    return new #rest(out!) as #output;
-                },
-            }
-        };
+        // the receiver of a struct passed by value is a copy of its fields
+        let by_value = matches!(inputs.first(), Some(RustType::Struct(_)));
 
         if let Some(assoc) = assoc {
             stmts.extend(match assoc {
+                // the constructor wraps the pointer to the new value
+                Association::Static if self.attr.has_constructor() => {
+                    ident = quote! { constructor };
+                    quote! {
+                        #call_expr(#(#call_args),*);
+                    }
+                },
                 Association::Static => {
                     ident = quote! { static #ident };
                     quote! {
@@ -180,6 +542,22 @@ impl TsMethod {
                     }
                 },
 
+                Association::Instance | Association::Destructor if by_value => {
+                    quote! {
+                        #call_expr(this.toBuffer(), #(#call_args),*);
+                    }
+                },
+                // rust may still use the receiver of a non-blocking call on
+                // another thread, so it is locked until the call settles
+                Association::Instance | Association::InstanceMut
+                    if self.attr.has_non_blocking() =>
+                {
+                    setup.extend(quote! { const ptr = this.lock(); });
+                    cleanup.extend(quote! { this.unlock(); });
+                    quote! {
+                        #call_expr(ptr, #(#call_args),*);
+                    }
+                },
                 Association::Instance => {
                     quote! {
                         #call_expr(this.ptr, #(#call_args),*);
@@ -201,14 +579,65 @@ impl TsMethod {
                     }
                 },
             });
-            stmts.extend(generate_return_stmt());
         } else {
             stmts.extend(quote! {
                 #call_expr(#(#call_args),*);
             });
-            stmts.extend(generate_return_stmt());
         }
+        // field accessors of a struct, and methods marked as accessors
+        if self.attr.has_get() || self.attr.has_getter() {
+            ident = quote! { get #ident };
+        } else if self.attr.has_set() || self.attr.has_setter() {
+            ident = quote! { set #ident };
+        }
+        if self.async_ {
+            stmts.extend(catch_panic(&quote! { task }));
+        } else if panic_slot {
+            stmts.extend(catch_slot_panic());
+        } else {
+            stmts.extend(catch_panic(&out));
+        }
+        // takes the result once the task is done
+        if self.async_ {
+            let await_ident = format_ident!("{ffi_ident}__await");
+            let (await_expr, catch_panic) = match output {
+                RustType::Void => (quote! { symbols.#await_ident }, catch_slot_panic()),
+                _ => (
+                    quote! { const #out = symbols.#await_ident },
+                    catch_panic(&out),
+                ),
+            };
+            stmts.extend(quote! {
+                await task_done.promise;
+                #await_expr(task, #(#await_args),*);
+                #catch_panic
+            });
+        }
+        if self.attr.has_constructor() {
+            stmts.extend(quote! { super(out!); });
+        } else {
+            stmts.extend(return_stmt(output));
+        }
+        if !callbacks.is_empty() || !cleanup.is_empty() {
+            stmts = quote! {
+                try {
+                    #stmts
+                } finally {
+                    #(if (#callbacks) #callbacks.close();)*
+                    #cleanup
+                }
+            };
+        }
+        stmts = quote! {
+            #setup
+            #stmts
+        };
 
+        let fn_output = if self.attr.has_constructor() {
+            TokenStream::new()
+        } else {
+            fn_output
+        };
         quote! {
             #ident(#(#fn_args),*) #fn_output {
                 #stmts
@@ -218,21 +647,168 @@ impl TsMethod {
     }
 }
 
+/// Allocates the buffers for the out-parameters of a shim's result, in the same
+/// order as the shim's trailing parameters
+fn out_buffers(output: &RustType, stmts: &mut TokenStream, call_args: &mut Vec<TokenStream>) {
+    match output {
+        // value of a tagged `Option<T>`
+        RustType::Option(elem) => {
+            if let Some(array) = elem.typed_array() {
+                stmts.extend(quote! { const out_value = new #array(1); });
+                call_args.push(quote! { out_value });
+            }
+        },
+        // length of a returned javascript string
+        RustType::JsString => {
+            stmts.extend(quote! { const out_len = new BigUint64Array(1); });
+            call_args.push(quote! { out_len });
+        },
+        // value of an `Ok` variant
+        RustType::Result(ok, _) => {
+            out_buffers(ok, stmts, call_args);
+            let array = match &**ok {
+                RustType::Void => return,
+                RustType::Option(elem) if elem.typed_array().is_some() => quote! { Uint8Array },
+                ok => ok.typed_array().unwrap_or(quote! { BigUint64Array }),
+            };
+            stmts.extend(quote! { const out_ok = new #array(1); });
+            call_args.push(quote! { out_ok });
+        },
+        _ => (),
+    }
+}
+
+/// Rethrows a panic caught by the last call to a shim. A shim returns a zeroed
+/// sentinel in place of its result `out` when it panics, so the message is only
+/// looked up for such a result
+pub fn catch_panic(out: &TokenStream) -> TokenStream {
+    quote! {
+        if (!#out) {
+            const panic = symbols.__RustPanic__take_message();
+            if (panic != null) {
+                const message = Deno.UnsafePointerView.getCString(panic);
+                symbols.__RustPanic__drop(panic);
+                throw new RustPanicError(message);
+            }
+        }
+    }
+}
+
+/// Rethrows a panic whose message a shim wrote to the `out_panic` buffer, for
+/// the calls that have no sentinel or run on another thread
+fn catch_slot_panic() -> TokenStream {
+    quote! {
+        if (out_panic[0] != 0n) {
+            const panic = Deno.UnsafePointer.create(out_panic[0]);
+            const message = Deno.UnsafePointerView.getCString(panic!);
+            symbols.__RustPanic__drop(panic);
+            throw new RustPanicError(message);
+        }
+    }
+}
+
+/// Converts the raw result `out` of a shim into its typescript representation
+fn return_stmt(output: &RustType) -> TokenStream {
+    match output {
+        RustType::Void => TokenStream::new(),
+        RustType::Numeric(_)
+        | RustType::Boolean
+        | RustType::FnPtr(..)
+        | RustType::Ptr(_)
+        | RustType::PtrMut(_)
+        | RustType::Ref(_)
+        | RustType::RefMut(_)
+        | RustType::Unsupported => quote! {
+            return out! as #output;
+        },
+        RustType::Option(elem) => {
+            if elem.typed_array().is_some() {
+                let value = match &**elem {
+                    RustType::Boolean => quote! { out_value[0] != 0 },
+                    RustType::Char => quote! { new RustChar(out_value[0]) },
+                    _ => quote! { out_value[0] },
+                };
+                quote! {
+                    return out ? #value : null;
+                }
+            } else if elem.is_prototype() {
+                let value = elem.construct(quote! { out });
+                quote! {
+                    return out == null ? null : #value;
+                }
+            } else {
+                quote! {
+                    return out as #output;
+                }
+            }
+        },
+        // 64-bit reprs are returned as a bigint
+        RustType::Enum(_) => quote! {
+            return Number(out) as #output;
+        },
+        RustType::JsString => quote! {
+            const len = Number(out_len[0]);
+            const value = len == 0
+                ? ""
+                : new TextDecoder().decode(Deno.UnsafePointerView.getArrayBuffer(out!, len));
+            symbols.__JsString__drop(out, out_len[0]);
+            return value;
+        },
+        RustType::Result(ok, class) => {
+            let mut stmts = quote! {
+                if (err != null) {
+                    const message = Deno.UnsafePointerView.getCString(err);
+                    symbols.__RustError__drop(err);
+                    throw new #class(message);
+                }
+            };
+            if **ok != RustType::Void {
+                let value = match &**ok {
+                    RustType::Numeric(_) | RustType::Char => quote! { out_ok[0] },
+                    RustType::Boolean => quote! { out_ok[0] != 0 },
+                    RustType::Option(elem) if elem.typed_array().is_some() => {
+                        quote! { out_ok[0] != 0 }
+                    },
+                    _ => quote! { Deno.UnsafePointer.create(out_ok[0]) },
+                };
+                stmts.extend(quote! { const out = #value; });
+            }
+            stmts.extend(return_stmt(ok));
+            stmts
+        },
+        rest => {
+            let value = rest.construct(quote! { out! });
+            quote! {
+                return #value as #output;
+            }
+        },
+    }
+}
+
 impl ToTokens for FunctionDefs {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         for method in &self.functions {
-            let async_ = if method.attr.has_non_blocking() {
+            let async_ = if method.async_ || method.attr.has_non_blocking() {
                 quote! { async }
             } else {
                 TokenStream::new()
             };
 
+            let doc = &method.doc;
             let method = method.print();
 
             tokens.extend(quote! {
+                #doc
                 export #async_ function #method
             });
         }
+        for (ident, namespace) in &self.namespaces {
+            tokens.extend(quote! {
+                export namespace #ident {
+                    #namespace
+                }
+            });
+        }
     }
 }
 
@@ -265,6 +841,9 @@ mod print_tests {
 
             let functions = TsFormat::format(module.functions.to_token_stream().to_string());
             println!("[ts mod]\n{}", functions);
+
+            let intern_defs = TsFormat::format(module.intern_defs.to_token_stream().to_string());
+            println!("[ts interned]\n{}", intern_defs);
         };
     }
 
@@ -292,6 +871,60 @@ mod print_tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_option() {
+        test_transform!(
+            fn test_fn(
+                arg0: Option<u8>,
+                arg1: Option<&CustomType>,
+                arg2: Option<String>,
+            ) -> Option<u64> {
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_result() {
+        test_transform!(
+            fn test_fn(arg0: u8) -> Result<Option<u8>, String> {}
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_tuple() {
+        test_transform!(
+            fn test_fn(arg0: (u8, String)) -> Option<(u8, char)> {}
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_vec() {
+        test_transform!(
+            fn test_fn(arg0: Vec<u32>) -> Vec<String> {}
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_buffer() {
+        test_transform!(
+            fn test_fn(arg0: &[u8], arg1: &mut [f64], arg2: &[char]) {}
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_js_string() {
+        test_transform!(
+            #[doc = "deno_bindgen_js_string"]
+            fn test_fn(arg0: &str, arg1: String, arg2: Option<String>) -> String {}
+        );
+    }
+
     #[test]
     #[cfg(feature = "cli")]
     fn test_many_args() {
@@ -299,4 +932,183 @@ mod print_tests {
             fn test_fn(arg0: &mut [u8], arg1: &str, arg2: Box<CustomType>, arg3: Vec<String>) {}
         );
     }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_fn_ptr() {
+        test_transform!(
+            fn test_fn(arg0: extern "C" fn(u8, *const u8) -> bool, arg1: u8) -> u8 {}
+        );
+
+        let source = parse_quote!(ItemFn, fn sort(arg0: extern "C" fn(f64, f64) -> i8) {});
+        let mut module = TsModule::default();
+        let method = source.unwrap(&mut module);
+        module.functions.push(method);
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains(r#"Deno.UnsafeCallbackFunction<["f64", "f64"], "i8">"#));
+        assert!(functions.contains(
+            r#"new Deno.UnsafeCallback({ parameters: ["f64", "f64"], result: "i8" } as const, arg_0)"#
+        ));
+        assert!(functions.contains(
+            "symbols.__sort(arg_0_callback ? arg_0_callback.pointer : arg_0, out_panic);"
+        ));
+        assert!(functions.contains("if (arg_0_callback) arg_0_callback.close();"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_closure() {
+        test_transform!(
+            fn test_fn(arg0: &dyn Fn(u8) -> bool, arg1: Box<dyn FnMut(f64) + Send>) {}
+        );
+
+        let source = parse_quote!(
+            ItemFn,
+            fn each(arg0: &dyn Fn(u32), arg1: Box<dyn Fn(f64, f64) -> i8>) {}
+        );
+        let mut module = TsModule::default();
+        let method = source.unwrap(&mut module);
+        module.functions.push(method);
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains(r#"arg_0: Deno.UnsafeCallbackFunction<["u32"], "void">"#));
+        assert!(
+            functions.contains(r#"{ parameters: ["pointer", "u32"], result: "void" } as const"#)
+        );
+        assert!(functions.contains("(_ctx, value_0) => arg_0(value_0)"));
+        assert!(functions.contains("Deno.UnsafeCallback.threadSafe("));
+        assert!(functions.contains("RustCallbacks.register(arg_1, arg_1_callback)"));
+        assert!(functions.contains("if (arg_0_callback) arg_0_callback.close();"));
+        assert!(!functions.contains("arg_1_callback.close()"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_async() {
+        test_transform!(
+            async fn test_fn(arg0: String) -> Option<u32> {}
+        );
+
+        let source = parse_quote!(ItemFn, async fn fetch(arg0: u32) -> Result<u32, String> {});
+        let mut module = TsModule::default();
+        module.crate_name = "deno_bindgen2_test".to_string();
+        let method = source.unwrap(&mut module);
+        module.functions.push(method);
+
+        let ffi_lib = TsFormat::format(module.ffi_lib.to_token_stream().to_string());
+        assert!(ffi_lib.contains(r#"name: "deno_bindgen2_test::__fetch","#));
+        assert!(ffi_lib.contains(r#"name: "deno_bindgen2_test::__fetch__await","#));
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains("export async function fetch(arg_0: RustU32): Promise<RustU32>"));
+        assert!(functions.contains("const task = symbols.__fetch(arg_0, task_callback.pointer);"));
+        assert!(functions.contains("await task_done.promise;"));
+        assert!(functions.contains("const err = symbols.__fetch__await(task, out_ok);"));
+        assert!(functions.contains("if (task_callback) task_callback.close();"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_async_fn_ptr() {
+        let source = parse_quote!(ItemFn, async fn poll(arg0: extern "C" fn(u32) -> bool) {});
+        let mut module = TsModule::default();
+        let method = source.unwrap(&mut module);
+        module.functions.push(method);
+
+        // the task calls the callback from a worker thread
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains(
+            r#"Deno.UnsafeCallback.threadSafe({ parameters: ["u32"], result: "bool" } as const, arg_0)"#
+        ));
+        assert!(!functions.contains("new Deno.UnsafeCallback"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_catch_panic() {
+        let mut module = TsModule::default();
+        for source in [
+            parse_quote!(ItemFn, fn len() -> usize {}),
+            parse_quote!(ItemFn, fn reset() {}),
+        ] {
+            let method = source.unwrap(&mut module);
+            module.functions.push(method);
+        }
+
+        let ffi_lib = TsFormat::format(module.ffi_lib.to_token_stream().to_string());
+        assert!(ffi_lib.contains(r#"parameters: ["buffer"], result: "void""#));
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        // the message is only taken for a zeroed result
+        assert!(functions.contains(
+            "const out = symbols.__len();
+    if (!out) {
+        const panic = symbols.__RustPanic__take_message();"
+        ));
+        // a call without a result has its message written to a buffer
+        assert!(functions.contains("symbols.__reset(out_panic);"));
+        assert!(functions.contains("if (out_panic[0] != 0n) {"));
+        assert_eq!(functions.matches("__RustPanic__take_message").count(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_cancellable() {
+        let source = parse_quote!(
+            ItemFn,
+            #[doc = "deno_bindgen_non_blocking"]
+            #[doc = "deno_bindgen_cancellable"]
+            fn count(arg0: u32, token: &CancellationToken) -> u32 {}
+        );
+        let mut module = TsModule::default();
+        module.crate_name = "deno_bindgen2_test".to_string();
+        let method = source.unwrap(&mut module);
+        module.functions.push(method);
+
+        let ffi_lib = TsFormat::format(module.ffi_lib.to_token_stream().to_string());
+        assert!(ffi_lib.contains(r#"name: "deno_bindgen2_test::__count","#));
+        assert!(ffi_lib.contains(r#"name: "deno_bindgen2_utils::__CancellationToken__cancel","#));
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.contains(
+            "export async function count(arg_0: RustU32, signal?: AbortSignal): Promise<RustU32>"
+        ));
+        assert!(functions.contains("signal.addEventListener(\"abort\", cancel, { once: true });"));
+        assert!(functions.contains("const out = await symbols.__count(arg_0, token, out_panic);"));
+        assert!(functions.contains("symbols.__RustPanic__drop(panic);"));
+        assert!(!functions.contains("__RustPanic__take_message"));
+        assert!(functions.contains("if (signal) signal.removeEventListener(\"abort\", cancel);"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_jsdoc() {
+        let source = parse_quote!(
+            ItemFn,
+            /// Divides two numbers
+            ///
+            /// Fails on a */ zero divisor
+            #[deprecated(note = "use `checked_div` instead")]
+            fn div(lhs: u32, _: (), rhs: std::primitive::u32) -> Result<Vec<u32>, String> {}
+        );
+        let mut module = TsModule::default();
+        let method = source.unwrap(&mut module);
+        module.functions.push(method);
+
+        let functions = TsFormat::format(module.functions.to_token_stream().to_string());
+        assert!(functions.starts_with(
+            "/**
+ * Divides two numbers
+ *
+ * Fails on a *\\/ zero divisor
+ *
+ * @param arg_0 - `lhs: u32`
+ * @param arg_1 - `rhs: u32`
+ * @returns `Vec<u32>`
+ * @throws {StringError} the `String` of an `Err`
+ * @deprecated use `checked_div` instead
+ */
+export function div("
+        ));
+    }
 }
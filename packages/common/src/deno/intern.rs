@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use crate::deno::util::*;
+use crate::deno::{TsMethod, TsModule};
+use crate::rust::{ItemInterned, Type};
+
+/// The accessor functions of an interned type. Printed as a class of static
+/// methods which is passed to `RustTuple` and `RustVec` instances as their
+/// vtable
+#[derive(Clone, Debug)]
+pub struct TsInterned {
+    /// number of elements of a tuple type
+    pub arity:   Option<usize>,
+    pub methods: Vec<TsMethod>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InternDefs {
+    pub store: BTreeMap<Ident, TsInterned>,
+}
+
+impl InternDefs {
+    /// Declares the accessor symbols of a tuple or `Vec<T>` type if it was not
+    /// interned yet and returns the name of its vtable class
+    pub fn intern(ty: &Type, module: &mut TsModule) -> Ident {
+        let item_interned = ItemInterned::new(ty);
+        let ident = item_interned.ident().clone();
+
+        if !module.intern_defs.store.contains_key(&ident) {
+            // reserve the name first, as the accessors refer back to this type
+            let arity = match &item_interned {
+                ItemInterned::Tuple(item_tuple) => Some(item_tuple.elems.len()),
+                ItemInterned::Vec(_) => None,
+            };
+            module.intern_defs.store.insert(ident.clone(), TsInterned {
+                arity,
+                methods: Vec::new(),
+            });
+
+            let methods = item_interned
+                .items()
+                .into_iter()
+                .map(|item| item.unwrap(module))
+                .collect();
+            module.intern_defs.store.get_mut(&ident).unwrap().methods = methods;
+        }
+
+        ident
+    }
+}
+
+impl ToTokens for InternDefs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        for (ident, TsInterned { arity, methods }) in &self.store {
+            let arity = arity.map(|arity| {
+                let arity = proc_macro2::Literal::usize_unsuffixed(arity);
+                quote! { static readonly arity = #arity; }
+            });
+            let methods = methods.iter().map(|method| method.print());
+
+            tokens.extend(quote! {
+                export class #ident {
+                    #arity
+                    #(#methods)*
+                }
+            });
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: transform tests
+
+#[cfg(test)]
+mod transform_tests {
+    use super::*;
+    use crate::deno::ty::RustTypeNumeric;
+    use crate::deno::{FfiType, RustType};
+    use crate::parse_quote;
+
+    #[test]
+    fn test_intern_tuple() {
+        let mut module = TsModule::default();
+        let (ffi_type, ty) = parse_quote!(Type, (u8, Box<(u8, String)>)).unwrap(&mut module);
+
+        assert_eq!(ffi_type, FfiType::Pointer);
+        assert_eq!(
+            ty,
+            RustType::Tuple(
+                vec![
+                    RustType::Numeric(RustTypeNumeric::U8),
+                    RustType::Box(Box::new(RustType::Tuple(
+                        vec![RustType::Numeric(RustTypeNumeric::U8), RustType::String],
+                        Some(format_ident!("Tuple2_u8_String"))
+                    )))
+                ],
+                Some(format_ident!("Tuple2_u8_Box_Tuple2_u8_String"))
+            )
+        );
+
+        let idents: Vec<String> = module
+            .intern_defs
+            .store
+            .keys()
+            .map(|ident| ident.to_string())
+            .collect();
+        assert_eq!(idents, [
+            "Tuple2_u8_Box_Tuple2_u8_String",
+            "Tuple2_u8_String"
+        ]);
+
+        for symbol in [
+            "__Tuple2_u8_String__new",
+            "__Tuple2_u8_String__get_0",
+            "__Tuple2_u8_String__get_1",
+            "__Tuple2_u8_String__drop",
+        ] {
+            assert!(module
+                .ffi_lib
+                .interface
+                .contains(&format_ident!("{symbol}")));
+        }
+    }
+
+    #[test]
+    fn test_intern_vec() {
+        let mut module = TsModule::default();
+        let (_, ty) = parse_quote!(Type, Vec<Vec<u32>>).unwrap(&mut module);
+
+        assert_eq!(
+            ty,
+            RustType::Vec(
+                Box::new(RustType::Vec(
+                    Box::new(RustType::Numeric(RustTypeNumeric::U32)),
+                    Some(format_ident!("RustVec_u32"))
+                )),
+                Some(format_ident!("RustVec_Vec_u32"))
+            )
+        );
+
+        for symbol in [
+            "__RustVec_u32__new",
+            "__RustVec_u32__len",
+            "__RustVec_u32__get",
+            "__RustVec_u32__push",
+            "__RustVec_u32__drop",
+            "__RustVec_Vec_u32__get",
+        ] {
+            assert!(module
+                .ffi_lib
+                .interface
+                .contains(&format_ident!("{symbol}")));
+        }
+    }
+
+    #[test]
+    fn test_opaque() {
+        let mut module = TsModule::default();
+        let (_, ty) = parse_quote!(Type, (u8, &str)).unwrap(&mut module);
+        assert!(matches!(ty, RustType::Tuple(_, None)));
+        let (_, ty) = parse_quote!(Type, Vec<&str>).unwrap(&mut module);
+        assert!(matches!(ty, RustType::Vec(_, None)));
+        assert!(module.intern_defs.store.is_empty());
+    }
+
+    #[test]
+    fn test_print() {
+        let mut module = TsModule::default();
+        parse_quote!(Type, (u8, String)).unwrap(&mut module);
+        parse_quote!(Type, Vec<String>).unwrap(&mut module);
+        println!(
+            "{}",
+            TsFormat::format(module.intern_defs.to_token_stream().to_string())
+        );
+    }
+}
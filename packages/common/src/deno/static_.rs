@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::deno::util::*;
+use crate::deno::{FfiStatic, FfiType, TsModule};
+use crate::rust::{ItemStatic, Type};
+
+/// Container for the `const` and `static` items of a crate, printed by their
+/// name
+#[derive(Clone, Debug, Default)]
+pub struct ConstDefs {
+    pub store:      BTreeMap<Ident, TokenStream>,
+    /// constants of the rust modules, printed as typescript namespaces
+    pub namespaces: BTreeMap<Ident, ConstDefs>,
+}
+
+impl ConstDefs {
+    /// The constants of a nested namespace, declaring it if needed
+    pub fn namespace(&mut self, path: &[Ident]) -> &mut Self {
+        match path.split_first() {
+            Some((ident, rest)) => self
+                .namespaces
+                .entry(ident.clone())
+                .or_default()
+                .namespace(rest),
+            None => self,
+        }
+    }
+}
+
+impl ItemStatic {
+    pub fn unwrap(self, module: &mut TsModule) {
+        let ident = module.ts_name(&self.attr, &self.ident);
+        let doc = JsDoc::new(&self.attr);
+
+        // literals are written into the bindings as they are
+        if self.is_inlined() {
+            let (ty, value) = match self.ty {
+                Type::Ref(_) => (quote! { string }, ts_literal(&self.expr, false)),
+                ty => {
+                    let (ffi_type, ty) = ty.unwrap(module);
+                    let bigint = matches!(
+                        ffi_type,
+                        FfiType::U64 | FfiType::I64 | FfiType::Usize | FfiType::Isize
+                    );
+                    (ty.to_token_stream(), ts_literal(&self.expr, bigint))
+                },
+            };
+            let namespace = module.namespace();
+            let const_defs = module.const_defs.namespace(&namespace);
+            const_defs.store.insert(ident.clone(), quote! {
+                #doc
+                export const #ident: #ty = #value;
+            });
+            return;
+        }
+
+        // other values are read on every access, as a `static mut` may change
+        // and the library may not be loaded yet
+        let (shim, shim_name) = module.symbol(&module.mod_path, &self.shim_ident());
+        module.ffi_lib.interface.push_static(FfiStatic {
+            ident: shim.clone(),
+            name:  shim_name,
+            ty:    FfiType::Pointer,
+        });
+        if let Type::UserDefined(ty) = &self.ty {
+            panic!("`{ident}` cannot be exported. `{ty}` is not a `repr(C)` struct whose fields can be passed by value");
+        }
+        let (ffi_type, ty) = self.ty.unwrap(module);
+        let value = match ffi_type {
+            FfiType::Bool => quote! { new Deno.UnsafePointerView(symbols.#shim!).getBool() },
+            FfiType::Struct(_) => quote! {
+                #ty.from(new Uint8Array(Deno.UnsafePointerView.getArrayBuffer(symbols.#shim!, #ty.size)))
+            },
+            ffi_type => {
+                let get = format_ident!("get{}", ffi_type.accessor().0);
+                quote! { new Deno.UnsafePointerView(symbols.#shim!).#get() }
+            },
+        };
+        let namespace = module.namespace();
+        let const_defs = module.const_defs.namespace(&namespace);
+        const_defs.store.insert(ident.clone(), quote! {
+            #doc
+            export const #ident = {
+                get value(): #ty {
+                    return #value;
+                },
+            };
+        });
+    }
+}
+
+/// Converts a rust literal into a typescript literal. 64-bit integers are
+/// written as a bigint
+fn ts_literal(expr: &syn::Expr, bigint: bool) -> TokenStream {
+    match expr {
+        syn::Expr::Unary(syn::ExprUnary { expr, .. }) => {
+            let value = ts_literal(expr, bigint);
+            quote! { -#value }
+        },
+        syn::Expr::Lit(syn::ExprLit { lit, .. }) => match lit {
+            syn::Lit::Int(lit_int) if bigint => {
+                TokenStream::from_str(&format!("{}n", lit_int.base10_digits())).unwrap()
+            },
+            syn::Lit::Int(lit_int) => TokenStream::from_str(lit_int.base10_digits()).unwrap(),
+            syn::Lit::Float(lit_float) => TokenStream::from_str(lit_float.base10_digits()).unwrap(),
+            syn::Lit::Str(lit_str) => js_string(&lit_str.value()),
+            lit => lit.to_token_stream(),
+        },
+        _ => unreachable!("not an inlined literal"),
+    }
+}
+
+/// Writes a string as a javascript string literal. The literal is printed from
+/// a token, so only the escapes shared by rust and javascript are used
+fn js_string(value: &str) -> TokenStream {
+    let mut lit = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => lit.push_str("\\\""),
+            '\\' => lit.push_str("\\\\"),
+            '\n' => lit.push_str("\\n"),
+            '\r' => lit.push_str("\\r"),
+            '\t' => lit.push_str("\\t"),
+            // line terminators are not allowed in a literal before es2019
+            c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+                lit.push_str(&format!("\\u{{{:x}}}", c as u32));
+            },
+            c => lit.push(c),
+        }
+    }
+    lit.push('"');
+    TokenStream::from_str(&lit).unwrap()
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: print
+
+impl ToTokens for ConstDefs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(self.store.values().cloned());
+        for (ident, namespace) in &self.namespaces {
+            tokens.extend(quote! {
+                export namespace #ident {
+                    #namespace
+                }
+            });
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deno::{RenameRule, TsFormat};
+    use crate::parse_quote;
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_inlined() {
+        let mut module = TsModule::default();
+        for source in [
+            parse_quote!(ItemStatic, const MAX: u32 = 0xff;),
+            parse_quote!(ItemStatic, const MIN: i64 = -1_000i64;),
+            parse_quote!(ItemStatic, const RATIO: f64 = 1.5;),
+            parse_quote!(ItemStatic, const ENABLED: bool = true;),
+            parse_quote!(ItemStatic, static NAME: &'static str = "deno \"bindgen\"";),
+            parse_quote!(ItemStatic, const DIR: &str = r"C:\dir\n";),
+            parse_quote!(ItemStatic, const LINES: &str = "a\tb\0c\u{2028}";),
+        ] {
+            source.unwrap(&mut module);
+        }
+
+        let const_defs = TsFormat::format(module.const_defs.to_token_stream().to_string());
+        assert!(const_defs.contains("export const MAX: RustU32 = 255;"));
+        assert!(const_defs.contains("export const MIN: RustI64 = -1000n;"));
+        assert!(const_defs.contains("export const RATIO: RustF64 = 1.5;"));
+        assert!(const_defs.contains("export const ENABLED: boolean = true;"));
+        assert!(const_defs.contains(r#"export const NAME: string = "deno \"bindgen\"";"#));
+        // raw strings are written with the escapes javascript expects
+        assert!(const_defs.contains(r#"export const DIR: string = "C:\\dir\\n";"#));
+        assert!(const_defs.contains(r#"export const LINES: string = "a\tb\u{0}c\u{2028}";"#));
+        assert!(module.ffi_lib.interface.symbols.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_static_mut() {
+        let mut module = TsModule::default();
+        module.crate_name = "deno_bindgen2_test".to_string();
+        parse_quote!(ItemStatic, static mut COUNT: u64 = 0;).unwrap(&mut module);
+
+        let ffi_lib = TsFormat::format(module.ffi_lib.to_token_stream().to_string());
+        assert!(ffi_lib
+            .contains(r#"__COUNT: { name: "deno_bindgen2_test::__COUNT", type: "pointer" }"#));
+
+        let const_defs = TsFormat::format(module.const_defs.to_token_stream().to_string());
+        assert!(const_defs.contains("get value(): RustU64 {"));
+        assert!(const_defs
+            .contains("return new Deno.UnsafePointerView(symbols.__COUNT!).getBigUint64();"));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_rename_all() {
+        let mut module = TsModule::default();
+        module.rename_all = Some(RenameRule::CamelCase);
+        parse_quote!(ItemStatic, const MAX_COUNT: u32 = 8;).unwrap(&mut module);
+        parse_quote!(
+            ItemStatic,
+            #[doc = "deno_bindgen_rename:LIMIT"]
+            const MAX_LIMIT: u32 = 4;
+        )
+        .unwrap(&mut module);
+
+        let const_defs = TsFormat::format(module.const_defs.to_token_stream().to_string());
+        assert!(const_defs.contains("export const maxCount: RustU32 = 8;"));
+        assert!(const_defs.contains("export const LIMIT: RustU32 = 4;"));
+    }
+}
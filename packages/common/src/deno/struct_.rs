@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+
+use crate::deno::util::*;
+use crate::deno::{FfiType, RustType, TsMethod, TsModule};
+use crate::rust::{Item, ItemStruct, StructTable, Type};
+
+/// A field of a struct passed by value, at its offset in the struct's layout
+#[derive(Clone, Debug)]
+pub struct TsField {
+    pub doc:      JsDoc,
+    pub ident:    Ident,
+    pub ty:       RustType,
+    pub ffi_type: FfiType,
+    pub offset:   usize,
+}
+
+/// A struct passed by value. Printed as a class which packs and unpacks the
+/// `Uint8Array` layout used by deno's struct ffi type
+#[derive(Clone, Debug)]
+pub struct TsStruct {
+    /// name of the class in typescript, within its namespace
+    pub path:     TsPath,
+    pub doc:      JsDoc,
+    pub fields:   Vec<TsField>,
+    pub size:     usize,
+    pub ffi_type: FfiType,
+    pub methods:  Vec<TsMethod>,
+    /// members printed after the methods by their name
+    pub members:  BTreeMap<String, TokenStream>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StructDefs {
+    /// structs passed by value that were not declared yet
+    pub pending: BTreeMap<Ident, ItemStruct>,
+    pub store:   BTreeMap<Ident, TsStruct>,
+}
+
+impl StructDefs {
+    /// Stores the structs passed by value of a crate, which are declared once
+    /// they are first used
+    pub fn collect(&mut self, items: &[Item], structs: &StructTable) {
+        for item in items {
+            match item {
+                Item::Struct(item_struct) if structs.contains(&item_struct.ident) => {
+                    self.pending
+                        .insert(item_struct.ident.clone(), item_struct.clone());
+                },
+                Item::Mod(item_mod) => self.collect(&item_mod.items, structs),
+                _ => (),
+            }
+        }
+    }
+
+    /// Declares a struct passed by value if it was not declared yet and returns
+    /// its ffi type. Returns `None` for other user-defined types
+    pub fn declare(ident: &Ident, module: &mut TsModule) -> Option<FfiType> {
+        if let Some(item_struct) = module.struct_defs.pending.remove(ident) {
+            let mut fields = Vec::new();
+            let mut ffi_types = Vec::new();
+            for (i, field) in item_struct.fields.into_iter().enumerate() {
+                // the struct table only admits structs whose nested structs are
+                // passed by value as well
+                let ty = match field.ty {
+                    Type::UserDefined(ident) => Type::Struct(ident),
+                    ty => ty,
+                };
+                let (ffi_type, ty) = ty.unwrap(module);
+                ffi_types.push(ffi_type.clone());
+                let ident = field.ident.unwrap_or_else(|| format_ident!("_{i}"));
+                fields.push(TsField {
+                    doc: JsDoc::new(&field.attr),
+                    ident: module.ts_name(&field.attr, &ident),
+                    ty,
+                    ffi_type,
+                    offset: 0,
+                });
+            }
+
+            let (offsets, size, _) = FfiType::struct_layout(&ffi_types);
+            for (field, offset) in fields.iter_mut().zip(offsets) {
+                field.offset = offset;
+            }
+
+            module.struct_defs.store.insert(ident.clone(), TsStruct {
+                path: module.type_path(ident),
+                doc: JsDoc::new(&item_struct.attr),
+                fields,
+                size,
+                ffi_type: FfiType::Struct(ffi_types),
+                methods: Vec::new(),
+                members: BTreeMap::new(),
+            });
+        }
+
+        module
+            .struct_defs
+            .store
+            .get(ident)
+            .map(|ts_struct| ts_struct.ffi_type.clone())
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: print
+
+impl TsField {
+    /// Reads this field from the `view` and `buffer` of a struct
+    fn read(&self) -> TokenStream {
+        let TsField { ty, .. } = self;
+        let offset = proc_macro2::Literal::usize_unsuffixed(self.offset);
+        match self.ffi_type {
+            FfiType::Bool => quote! { view.getUint8(#offset) != 0 },
+            FfiType::Pointer => quote! {
+                Deno.UnsafePointer.create(view.getBigUint64(#offset, true)) as #ty
+            },
+            FfiType::Struct(_) => quote! {
+                #ty.from(buffer.subarray(#offset, #offset + #ty.size))
+            },
+            _ => {
+                let (accessor, endian) = self.ffi_type.accessor();
+                let get = format_ident!("get{accessor}");
+                if endian {
+                    quote! { view.#get(#offset, true) }
+                } else {
+                    quote! { view.#get(#offset) }
+                }
+            },
+        }
+    }
+
+    /// Writes this field into the `view` and `buffer` of a struct
+    fn write(&self) -> TokenStream {
+        let TsField { ident, .. } = self;
+        let offset = proc_macro2::Literal::usize_unsuffixed(self.offset);
+        match self.ffi_type {
+            FfiType::Bool => quote! { view.setUint8(#offset, Number(this.#ident)); },
+            FfiType::Pointer => quote! {
+                view.setBigUint64(#offset, BigInt(Deno.UnsafePointer.value(this.#ident)), true);
+            },
+            FfiType::Struct(_) => quote! { buffer.set(this.#ident.toBuffer(), #offset); },
+            _ => {
+                let (accessor, endian) = self.ffi_type.accessor();
+                let set = format_ident!("set{accessor}");
+                if endian {
+                    quote! { view.#set(#offset, this.#ident, true); }
+                } else {
+                    quote! { view.#set(#offset, this.#ident); }
+                }
+            },
+        }
+    }
+}
+
+impl ToTokens for StructDefs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let mut decls = Vec::new();
+        for TsStruct {
+            path,
+            doc,
+            fields,
+            size,
+            methods,
+            members,
+            ..
+        } in self.store.values()
+        {
+            let ident = &path.ident;
+            let size = proc_macro2::Literal::usize_unsuffixed(*size);
+            let field_idents: Vec<&Ident> = fields.iter().map(|field| &field.ident).collect();
+            let field_types: Vec<&RustType> = fields.iter().map(|field| &field.ty).collect();
+            let field_docs = fields.iter().map(|field| &field.doc);
+            let reads = fields.iter().map(|field| field.read());
+            let writes = fields.iter().map(|field| field.write());
+            let methods = methods.iter().map(|method| {
+                let doc = &method.doc;
+                let method = method.print();
+                quote! { #doc #method }
+            });
+            let members = members.values();
+
+            decls.push((path.namespace.clone(), quote! {
+                #doc
+                export class #ident {
+                    #(#field_docs #field_idents: #field_types;)*
+                    static readonly size = #size;
+
+                    constructor(#(#field_idents: #field_types),*) {
+                        #(this.#field_idents = #field_idents;)*
+                    }
+
+                    static from(buffer: Uint8Array): #ident {
+                        const view = new DataView(buffer.buffer, buffer.byteOffset, buffer.byteLength);
+                        return new #ident(#(#reads),*);
+                    }
+
+                    toBuffer(): Uint8Array {
+                        const buffer = new Uint8Array(#ident.size);
+                        const view = new DataView(buffer.buffer);
+                        #(#writes)*
+                        return buffer;
+                    }
+
+                    #(#methods)*
+                    #(#members)*
+                }
+            }));
+        }
+        tokens.extend(print_namespaced(decls));
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deno::{CodegenOpts, TsFormat};
+    use crate::rust::File;
+
+    fn module(source: TokenStream) -> TsModule {
+        let file = File::parse_str(source.to_string().as_str());
+        TsModule::new(file, &CodegenOpts {
+            crate_name: "deno_bindgen2_test".to_string(),
+            file_name:  String::new(),
+            dylib_path: String::new(),
+            lazy:       false,
+            extended:   false,
+            embedded:   None,
+            js_string:  false,
+            namespaces: false,
+            rename_all: None,
+        })
+    }
+
+    #[test]
+    fn test_declare() {
+        let module = module(quote! {
+            #[doc = "deno_bindgen"]
+            #[repr(C)]
+            struct Line(Point, Point, bool);
+
+            #[doc = "deno_bindgen"]
+            #[repr(C)]
+            struct Point { x: f64, y: u8 }
+
+            #[doc = "deno_bindgen"]
+            fn length(line: &Line) -> f64 {}
+        });
+
+        let line = &module.struct_defs.store[&format_ident!("Line")];
+        let offsets: Vec<usize> = line.fields.iter().map(|field| field.offset).collect();
+        assert_eq!(offsets, [0, 16, 32]);
+        assert_eq!(line.size, 40);
+
+        let point = FfiType::Struct(vec![FfiType::F64, FfiType::U8]);
+        assert_eq!(module.functions.functions[0].inputs, [RustType::Struct(
+            TsPath {
+                namespace: Vec::new(),
+                ident:     format_ident!("Line"),
+            }
+        )]);
+        assert!(module.ffi_lib.interface.symbols.iter().any(|symbol| {
+            *symbol
+                == crate::deno::ffi::FfiSymbol::Function(crate::deno::FfiFunction {
+                    ident:        format_ident!("__length"),
+                    name:         "deno_bindgen2_test::__length".to_string(),
+                    inputs:       vec![FfiType::Struct(vec![
+                        point.clone(),
+                        point.clone(),
+                        FfiType::Bool,
+                    ])],
+                    output:       FfiType::F64,
+                    non_blocking: false,
+                })
+        }));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_print() {
+        let module = module(quote! {
+            #[doc = "deno_bindgen"]
+            #[repr(C)]
+            struct Point { x: f64, y: f64, flag: bool, ptr: *const u8 }
+
+            #[doc = "deno_bindgen"]
+            impl Point {
+                fn new(x: f64, y: f64) -> Point {}
+                fn length(&self) -> f64 {}
+            }
+
+            #[doc = "deno_bindgen"]
+            fn add(a: Point, b: Point) -> Point {}
+        });
+
+        println!(
+            "{}",
+            TsFormat::format(module.ffi_lib.to_token_stream().to_string())
+        );
+        println!(
+            "{}",
+            TsFormat::format(module.struct_defs.to_token_stream().to_string())
+        );
+        println!(
+            "{}",
+            TsFormat::format(module.functions.to_token_stream().to_string())
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 
 use crate::deno::util::*;
-use crate::deno::{ClassDefs, FfiInterface, FfiType, TsFormat, TsModule};
+use crate::deno::{FfiCallback, FfiInterface, FfiType, InternDefs, StructDefs, TsFormat, TsModule};
 use crate::rust::{Type, TypeNumeric};
 
 /* -------------------------------------------------------------------------- */
@@ -30,18 +30,25 @@ pub enum RustType {
     Numeric(RustTypeNumeric),
     Boolean,
     Char,
-    FnPtr(String), // pointer object containing actual reference value and signature value
+    FnPtr(String, FfiCallback), // the signature of the pointer and its callback definition
+    Closure(FfiCallback, bool), // the callback definition of a closure, and whether it is boxed
     Ptr(Box<RustType>),
     PtrMut(Box<RustType>),
     Ref(Box<RustType>),
     RefMut(Box<RustType>),
     Box(Box<RustType>),
+    Option(Box<RustType>),
+    Result(Box<RustType>, Ident), // throws the `RustError` subclass of its error type
     Str,
     String,
     Slice(Box<RustType>),
-    Vec(Box<RustType>),
-    Tuple(Vec<RustType>),
-    UserDefined(Ident),
+    TypedArray(Box<RustType>), // a numeric slice borrowed from a typed array without copying
+    JsString,                  // a `&str` or `String` passed as a javascript string
+    Vec(Box<RustType>, Option<Ident>), // the vtable class of an interned `Vec<T>`
+    Tuple(Vec<RustType>, Option<Ident>), // the vtable class of an interned tuple
+    Struct(TsPath),            // a `repr(C)` struct passed by value
+    Enum(TsPath),              // a C-like enum passed as its integer repr
+    UserDefined(TsPath),
     Unsupported, // generic deno pointer object
 }
 
@@ -51,11 +58,7 @@ pub enum RustType {
 
 impl Type {
     pub fn unwrap(self, module: &mut TsModule) -> (FfiType, RustType) {
-        let TsModule {
-            type_defs,
-            user_defs,
-            ..
-        } = module;
+        let TsModule { type_defs, .. } = module;
 
 
         fn match_str_or_slice(
@@ -136,11 +139,29 @@ impl Type {
             },
             Type::FnPtr(type_bare_fn) => {
                 type_defs.insert(RustTypeList::FnPtr);
+                let callback = FfiCallback::new(&type_bare_fn, module);
                 (
                     FfiType::FnPointer,
-                    RustType::FnPtr(type_bare_fn.to_token_stream().to_string()),
+                    RustType::FnPtr(type_bare_fn.to_token_stream().to_string(), callback),
                 )
             },
+            Type::Option(elem) => {
+                let ffi_type = if elem.is_buffer_elem() {
+                    FfiType::Buffer
+                } else {
+                    FfiType::Pointer
+                };
+                (ffi_type, RustType::Option(Box::new(elem.unwrap(module).1)))
+            },
+            Type::Enum(ident, repr) => {
+                let (ffi_type, _) = Type::Numeric(repr).unwrap(module);
+                (ffi_type, RustType::Enum(module.type_path(&ident)))
+            },
+            Type::Struct(ident) => {
+                let ffi_type = StructDefs::declare(&ident, module)
+                    .expect("struct passed by value is missing from the struct table");
+                (ffi_type, RustType::Struct(module.type_path(&ident)))
+            },
             rest => (FfiType::Pointer, match rest {
                 Type::Ptr(elem) => match_str_or_slice(elem, module, &|elem, module| {
                     module.type_defs.insert(RustTypeList::Ptr);
@@ -166,6 +187,17 @@ impl Type {
                     module.type_defs.insert(RustTypeList::Box);
                     RustType::Box(Box::new(elem.unwrap(module).1))
                 }),
+                Type::Result(ok, err) => {
+                    type_defs.insert(RustTypeList::Result);
+                    module.ffi_lib.interface.push_util_fn(
+                        "__RustError__drop",
+                        vec![FfiType::Pointer],
+                        FfiType::Void,
+                    );
+                    let class = ErrorDefs::name(&err, module);
+                    module.error_defs.store.insert(class.clone());
+                    RustType::Result(Box::new(ok.unwrap(module).1), class)
+                },
                 Type::Str => {
                     type_defs.insert(RustTypeList::Str);
                     RustType::Str
@@ -184,21 +216,39 @@ impl Type {
                 },
                 Type::Vec(elem) => {
                     type_defs.insert(RustTypeList::Vec);
-                    RustType::Vec(Box::new(elem.unwrap(module).1))
+                    let vec = Type::Vec(elem);
+                    let vtable = if vec.is_interned() {
+                        Some(InternDefs::intern(&vec, module))
+                    } else {
+                        None
+                    };
+                    let Type::Vec(elem) = vec else { unreachable!() };
+                    RustType::Vec(Box::new(elem.unwrap(module).1), vtable)
                 },
                 Type::UserDefined(ident) => {
-                    user_defs.insert(ident.clone());
-                    RustType::UserDefined(ident)
+                    let path = module.type_path(&ident);
+                    module.user_defs.insert(path.clone());
+                    RustType::UserDefined(path)
                 },
                 Type::Tuple(elems) => {
                     type_defs.insert(RustTypeList::Tuple);
+                    let tuple = Type::Tuple(elems);
+                    let vtable = if tuple.is_interned() {
+                        Some(InternDefs::intern(&tuple, module))
+                    } else {
+                        None
+                    };
+                    let Type::Tuple(elems) = tuple else {
+                        unreachable!()
+                    };
                     let mut tup_elems = Vec::new();
                     for elem in elems {
                         tup_elems.push(elem.unwrap(module).1);
                     }
-                    RustType::Tuple(tup_elems)
+                    RustType::Tuple(tup_elems, vtable)
                 },
-                Type::Unsupported(_) => {
+                // closures are only passed as parameters (see `ItemFn::unwrap`)
+                Type::DynFn(_) | Type::Unsupported(_) => {
                     type_defs.insert(RustTypeList::Unsupported);
                     RustType::Unsupported
                 },
@@ -229,12 +279,106 @@ mod transform_tests {
     fn test_ts_type() {
         dbg_assert!(unwrap_type!(()), RustType::Void);
     }
+
+    #[test]
+    fn test_result() {
+        dbg_assert!(
+            unwrap_type!(Result<u8, String>),
+            RustType::Result(
+                Box::new(RustType::Numeric(RustTypeNumeric::U8)),
+                format_ident!("StringError")
+            )
+        );
+        dbg_assert!(
+            unwrap_type!(Result<(), ParseIntError>),
+            RustType::Result(Box::new(RustType::Void), format_ident!("ParseIntError"))
+        );
+    }
+
+    #[test]
+    fn test_option() {
+        dbg_assert!(
+            unwrap_type!(Option<u8>),
+            RustType::Option(Box::new(RustType::Numeric(RustTypeNumeric::U8)))
+        );
+        dbg_assert!(
+            unwrap_type!(Option<Box<u8>>),
+            RustType::Option(Box::new(RustType::Box(Box::new(RustType::Numeric(
+                RustTypeNumeric::U8
+            )))))
+        );
+    }
 }
 
 /* -------------------------------------------------------------------------- */
 
 // MARK: print
 
+impl RustType {
+    /// Whether this type is represented by a `RustPrototype` class that owns
+    /// its pointer and must be consumed with `take()` when passed to rust
+    pub fn is_prototype(&self) -> bool {
+        !matches!(
+            self,
+            RustType::Void
+                | RustType::Numeric(_)
+                | RustType::Boolean
+                | RustType::Char
+                | RustType::FnPtr(..)
+                | RustType::Closure(..)
+                | RustType::Ptr(_)
+                | RustType::PtrMut(_)
+                | RustType::Ref(_)
+                | RustType::RefMut(_)
+                | RustType::Option(_)
+                | RustType::Result(..)
+                | RustType::TypedArray(_)
+                | RustType::JsString
+                | RustType::Struct(_)
+                | RustType::Enum(_)
+                | RustType::Unsupported
+        )
+    }
+
+    /// Wraps an owned pointer into the class representing this type. Interned
+    /// tuples and vecs are given their vtable
+    pub fn construct(&self, ptr: TokenStream) -> TokenStream {
+        match self {
+            RustType::Vec(_, Some(vtable)) | RustType::Tuple(_, Some(vtable)) => {
+                quote! { new #self(#ptr, #vtable) }
+            },
+            RustType::Struct(path) => quote! { #path.from(#ptr) },
+            // the constructor of a class may call into rust instead
+            RustType::UserDefined(path) => quote! { #path.wrap(#ptr) },
+            _ => quote! { new #self(#ptr) },
+        }
+    }
+
+    /// The typed array used to pass this type by value through a buffer
+    #[rustfmt::skip]
+    pub fn typed_array(&self) -> Option<TokenStream> {
+        Some(match self {
+            RustType::Numeric(numeric) => match numeric {
+                RustTypeNumeric::U8    => quote! { Uint8Array },
+                RustTypeNumeric::U16   => quote! { Uint16Array },
+                RustTypeNumeric::U32   => quote! { Uint32Array },
+                RustTypeNumeric::I8    => quote! { Int8Array },
+                RustTypeNumeric::I16   => quote! { Int16Array },
+                RustTypeNumeric::I32   => quote! { Int32Array },
+                RustTypeNumeric::F32   => quote! { Float32Array },
+                RustTypeNumeric::F64   => quote! { Float64Array },
+                RustTypeNumeric::U64   => quote! { BigUint64Array },
+                RustTypeNumeric::I64   => quote! { BigInt64Array },
+                RustTypeNumeric::Usize => quote! { BigUint64Array },
+                RustTypeNumeric::Isize => quote! { BigInt64Array },
+            },
+            RustType::Boolean => quote! { Uint8Array },
+            RustType::Char => quote! { Uint32Array },
+            _ => return None,
+        })
+    }
+}
+
 #[rustfmt::skip]
 impl ToTokens for RustTypeNumeric {
     fn to_tokens(&self, tokens: &mut TokenStream) {
@@ -271,7 +415,9 @@ impl ToTokens for RustType {
             RustType::Numeric(ts_type_numeric) => ts_type_numeric.to_token_stream(),
             RustType::Boolean => quote! { boolean },
             RustType::Char => quote! { RustChar },
-            RustType::FnPtr(sig) => quote! { RustFnPtr<#sig> },
+            RustType::FnPtr(sig, _) => quote! { RustFnPtr<#sig> },
+            RustType::Closure(FfiCallback { parameters, result }, _) =>
+            quote! { Deno.UnsafeCallbackFunction<[#(#parameters),*], #result> },
             RustType::Ptr(elem) =>
             get_str_or_slice(elem, quote! { RustPtr<#elem> }),
             RustType::PtrMut(elem) =>
@@ -282,12 +428,18 @@ impl ToTokens for RustType {
             get_str_or_slice(elem, quote! { RustRefMut<#elem> }),
             RustType::Box(elem) =>
             get_str_or_slice(elem, quote! { RustBox<#elem> }),
+            RustType::Option(elem) => quote! { #elem | null },
+            RustType::Result(ok, _) => ok.to_token_stream(),
             RustType::Str => quote! { RustStr },
             RustType::String => quote! { RustString },
             RustType::Slice(elem) => quote! { RustSlice<#elem> },
-            RustType::Vec(elem) => quote! { RustVec<#elem> },
-            RustType::Tuple(elem) => quote! { RustTuple<[#(#elem),*]> },
-            RustType::UserDefined(ident) => quote! { #ident },
+            RustType::TypedArray(elem) => elem.typed_array().expect("not a numeric slice"),
+            RustType::JsString => quote! { string },
+            RustType::Vec(elem, _) => quote! { RustVec<#elem> },
+            RustType::Tuple(elem, _) => quote! { RustTuple<[#(#elem),*]> },
+            RustType::Struct(path) => quote! { #path },
+            RustType::Enum(path) => quote! { #path },
+            RustType::UserDefined(path) => quote! { #path },
             RustType::Unsupported => quote! { RustUnsupportedType },
         });
     }
@@ -301,18 +453,18 @@ impl ToTokens for RustType {
 /// included in the list of declared structs
 #[derive(Clone, Debug, Default)]
 pub struct UserDefinedDefs {
-    store: BTreeSet<Ident>,
+    store: BTreeSet<TsPath>,
 }
 
 impl UserDefinedDefs {
-    fn insert(&mut self, ident: Ident) {
-        self.store.insert(ident);
+    fn insert(&mut self, path: TsPath) {
+        self.store.insert(path);
     }
 
     /// removes any user defined type from this list if it matches that from the
-    /// list of class definitions
-    pub fn dedup(&mut self, class_defs: &ClassDefs) {
-        class_defs.store.keys().for_each(|class_name| {
+    /// list of class or struct definitions
+    pub fn dedup<'a>(&mut self, declared: impl Iterator<Item = &'a TsPath>) {
+        declared.for_each(|class_name| {
             self.store
                 .extract_if(|user_defined| *user_defined == *class_name)
                 .for_each(drop);
@@ -322,25 +474,99 @@ impl UserDefinedDefs {
 
 impl ToTokens for UserDefinedDefs {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        tokens.extend(
+        tokens.extend(print_namespaced(
             self.store
                 .iter()
-                .map(|ident| {
-                    quote! {
+                .map(|TsPath { namespace, ident }| {
+                    (namespace.clone(), quote! {
 
                         export class #ident extends RustPrototype<#ident> {}
 
-                    }
+                    })
                 })
-                .collect::<TokenStream>(),
-        );
+                .collect(),
+        ));
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: errors
+
+/// Container for the `RustError` subclasses thrown for the error types of the
+/// `Result<T, E>` values of a crate, one per distinct `E`
+#[derive(Clone, Debug, Default)]
+pub struct ErrorDefs {
+    pub store: BTreeSet<Ident>,
+}
+
+impl ErrorDefs {
+    /// The error classes in scope of the generated module, which a subclass
+    /// must not shadow
+    const RESERVED: &[&str] = &[
+        "AggregateError",
+        "Error",
+        "EvalError",
+        "RangeError",
+        "ReferenceError",
+        "RustError",
+        "RustPanicError",
+        "SyntaxError",
+        "TypeError",
+        "URIError",
+    ];
+
+    /// The name of the subclass thrown for the error type `E`, i.e.
+    /// `ParseIntError` or `StringError` for `String`. Error types exported as a
+    /// class or named as a JS global error are suffixed as well, as that name
+    /// is taken
+    pub fn name(err: &Type, module: &TsModule) -> Ident {
+        let mut name: String = rust_text(err)
+            .split(|c: char| !c.is_alphanumeric())
+            .flat_map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .into_iter()
+                    .flat_map(char::to_uppercase)
+                    .chain(chars)
+            })
+            .collect();
+        if name.is_empty() {
+            name.push_str("Unit");
+        }
+        let declared = module
+            .type_paths
+            .keys()
+            .any(|ident| module.type_name(ident) == name);
+        let reserved = Self::RESERVED.contains(&name.as_str());
+        if declared || reserved || !name.ends_with("Error") {
+            name.push_str("Error");
+        }
+        format_ident!("{name}")
+    }
+}
+
+impl ToTokens for ErrorDefs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        for ident in &self.store {
+            let name = ident.to_string();
+            tokens.extend(quote! {
+                export class #ident extends RustError {
+                    constructor(message: string) {
+                        super(message);
+                        this.name = #name;
+                    }
+                }
+            });
+        }
     }
 }
 
 /* -------------------------------------------------------------------------- */
 
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
-enum RustTypeList {
+pub enum RustTypeList {
     U8,
     U16,
     U32,
@@ -365,6 +591,9 @@ enum RustTypeList {
     Slice,
     Vec,
     Tuple,
+    Result,
+    Panic,
+    Callback,
     Unsupported,
     // types not checked:
     // Void,
@@ -399,6 +628,9 @@ impl ToTokens for RustTypeList {
             RustTypeList::Slice => quote! { RustSlice },
             RustTypeList::Vec => quote! { RustVec },
             RustTypeList::Tuple => quote! { RustTuple },
+            RustTypeList::Result => quote! { RustError },
+            RustTypeList::Panic => quote! { RustPanicError },
+            RustTypeList::Callback => quote! { RustCallbacks },
             RustTypeList::Unsupported => quote! { RustUnsupportedType },
         });
     }
@@ -469,7 +701,7 @@ pub struct RustTypeDefs {
 }
 
 impl RustTypeDefs {
-    fn insert(&mut self, ty: RustTypeList) {
+    pub fn insert(&mut self, ty: RustTypeList) {
         self.store.insert(ty);
     }
 }
@@ -539,7 +771,10 @@ impl RustTypeDefs {
                 | RustTypeList::String
                 | RustTypeList::Slice
                 | RustTypeList::Vec
-                | RustTypeList::Tuple => class_imports.push(ty.to_token_stream()),
+                | RustTypeList::Tuple
+                | RustTypeList::Result
+                | RustTypeList::Panic
+                | RustTypeList::Callback => class_imports.push(ty.to_token_stream()),
                 rest => type_imports.push(rest.to_token_stream()),
             };
         }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use dprint_plugin_typescript::configuration::ConfigurationBuilder;
 use dprint_plugin_typescript::format_parsed_source;
 pub use proc_macro2::{Ident, TokenStream};
@@ -5,10 +7,188 @@ pub use quote::{format_ident, quote, ToTokens};
 pub use syn::parse::Parse;
 pub use syn::{braced, bracketed, LitStr, Token};
 
+use crate::rust::{Attribute, Type};
+
+/// The rust source of a type as it is written in documentation, i.e. `Vec<u8>`
+/// rather than the spacing and resolved paths of its tokens
+pub fn rust_text(ty: &Type) -> String {
+    let mut text = [
+        (" < ", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        ("& ", "&"),
+        (" ,", ","),
+        (" ;", ";"),
+        (" :: ", "::"),
+        (":: ", "::"),
+        ("fn (", "fn("),
+        ("Fn (", "Fn("),
+        ("FnMut (", "FnMut("),
+        ("FnOnce (", "FnOnce("),
+    ]
+    .iter()
+    .fold(ty.to_token_stream().to_string(), |text, (from, to)| {
+        text.replace(from, to)
+    });
+    // strips the leading segments of paths, i.e. `std::primitive::`
+    while let Some(end) = text.find("::") {
+        let start = text[..end]
+            .rfind(|c: char| !c.is_alphanumeric() && c != '_')
+            .map_or(0, |i| i + 1);
+        text.replace_range(start..end + 2, "");
+    }
+    text
+}
+
+/// The name a struct or enum is referred to by in typescript, qualified by the
+/// namespace it is declared in
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TsPath {
+    pub namespace: Vec<Ident>,
+    pub ident:     Ident,
+}
+
+impl ToTokens for TsPath {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let TsPath { namespace, ident } = self;
+        tokens.extend(quote! { #(#namespace.)* #ident });
+    }
+}
+
+/// Prints declarations in the `export namespace` blocks of their namespace.
+/// Declarations at the root are printed first
+pub fn print_namespaced(decls: Vec<(Vec<Ident>, TokenStream)>) -> TokenStream {
+    let mut tokens = TokenStream::new();
+    let mut namespaces: BTreeMap<Ident, Vec<(Vec<Ident>, TokenStream)>> = BTreeMap::new();
+    for (mut namespace, decl) in decls {
+        if namespace.is_empty() {
+            tokens.extend(decl);
+        } else {
+            let ident = namespace.remove(0);
+            namespaces.entry(ident).or_default().push((namespace, decl));
+        }
+    }
+    for (ident, decls) in namespaces {
+        let decls = print_namespaced(decls);
+        tokens.extend(quote! {
+            export namespace #ident {
+                #decls
+            }
+        });
+    }
+    tokens
+}
+
+/// The JSDoc block of a generated item, built from the doc comments of its rust
+/// item. Comments cannot be carried by a token stream, so the block is printed
+/// as a marker followed by its text, which is expanded by `TsFormat::format`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JsDoc {
+    pub lines:      Vec<String>,
+    /// note of the `@deprecated` tag, printed after every other tag
+    pub deprecated: Option<String>,
+}
+
+impl JsDoc {
+    const MARKER: &'static str = "__jsdoc__";
+
+    pub fn new(attr: &Attribute) -> Self {
+        let mut lines: Vec<String> = attr
+            .doc
+            .iter()
+            .map(|line| {
+                line.strip_prefix(' ')
+                    .unwrap_or(line)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        Self {
+            lines,
+            deprecated: attr.deprecated.clone(),
+        }
+    }
+
+    /// Appends a block tag such as `@param` or `@returns`, separated from the
+    /// description by an empty line
+    pub fn tag(&mut self, tag: &str, text: &str) {
+        if self.lines.last().is_some_and(|line| !line.starts_with('@')) {
+            self.lines.push(String::new());
+        }
+        self.lines
+            .push(format!("@{tag} {text}").trim_end().to_string());
+    }
+
+    /// Replaces the markers of a printed source with their JSDoc blocks
+    fn expand(source: String) -> String {
+        let mut expanded = String::with_capacity(source.len());
+        let mut rest = source.as_str();
+        while let Some(start) = rest.find(Self::MARKER) {
+            expanded.push_str(&rest[..start]);
+            rest = rest[start + Self::MARKER.len()..].trim_start();
+
+            // the string literal ends at the first unescaped quote
+            let mut escaped = false;
+            let end = rest
+                .char_indices()
+                .skip(1)
+                .find(|&(_, c)| {
+                    let end = c == '"' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    end
+                })
+                .map(|(i, _)| i + 1)
+                .expect("unterminated JSDoc marker");
+            let text = syn::parse_str::<LitStr>(&rest[..end])
+                .expect("failed to parse JSDoc marker")
+                .value();
+            rest = &rest[end..];
+
+            // a block on the line of the previous item would be attached to it
+            expanded.push_str("\n/**\n");
+            for line in text.lines() {
+                if line.is_empty() {
+                    expanded.push_str(" *\n");
+                } else {
+                    expanded.push_str(&format!(" * {line}\n"));
+                }
+            }
+            expanded.push_str(" */\n");
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+impl ToTokens for JsDoc {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let mut lines = self.lines.clone();
+        if let Some(note) = &self.deprecated {
+            let mut doc = Self {
+                lines,
+                deprecated: None,
+            };
+            doc.tag("deprecated", note);
+            lines = doc.lines;
+        }
+        if lines.is_empty() {
+            return;
+        }
+        // a line cannot close the comment early
+        let text = lines.join("\n").replace("*/", "*\\/");
+        let marker = format_ident!("{}", Self::MARKER);
+        tokens.extend(quote! { #marker #text });
+    }
+}
+
 pub struct TsFormat;
 
 impl TsFormat {
     pub fn format(source: String) -> String {
+        let source = JsDoc::expand(source);
         let module = deno_ast::parse_module(deno_ast::ParseParams {
             specifier:      deno_ast::ModuleSpecifier::parse("file://")
                 .expect("failed to parse ts source"),
@@ -1,4 +1,7 @@
-#![cfg_attr(feature = "macro", feature(proc_macro_diagnostic))]
+#![cfg_attr(
+    feature = "macro",
+    feature(proc_macro_diagnostic, proc_macro_tracked_env)
+)]
 #![cfg_attr(feature = "cli", feature(btree_extract_if))]
 
 #[cfg(feature = "macro")]
@@ -7,40 +10,58 @@ extern crate proc_macro;
 #[allow(unused_imports)]
 mod rust {
     mod attr;
+    mod enum_;
     mod file;
     mod fn_;
     mod impl_;
+    mod intern;
     mod item;
     mod mod_;
+    mod static_;
     mod struct_;
+    mod tuple;
     mod ty;
     mod util;
+    mod vec;
     pub use attr::{Attribute, Marker};
+    pub use enum_::{EnumTable, ItemEnum, Variant};
     pub use file::File;
-    pub use fn_::{Association, ItemFn};
-    pub use impl_::ItemImpl;
+    pub use fn_::{export_name, Association, Block, ItemFn};
+    pub use impl_::{ItemImpl, Protocol};
+    pub use intern::{assoc_fn, InternTable, ItemInterned};
     pub use item::Item;
     pub use mod_::ItemMod;
-    pub use struct_::ItemStruct;
-    pub use ty::{Type, TypeNumeric};
+    pub use static_::ItemStatic;
+    pub use struct_::{Field, ItemStruct, StructTable};
+    pub use tuple::ItemTuple;
+    pub use ty::{Type, TypeDynFn, TypeNumeric};
+    pub use vec::ItemVec;
 }
 
 #[cfg(feature = "cli")]
 mod deno {
     mod class;
+    mod enum_;
     mod ffi;
     mod file;
     mod fn_;
+    mod intern;
+    mod static_;
+    mod struct_;
     mod ty;
     mod util;
     pub use class::ClassDefs;
-    pub use ffi::{FfiFunction, FfiInterface, FfiLib, FfiType};
-    pub use file::{CodegenOpts, TsModule};
-    pub use fn_::{FunctionDefs, TsMethod};
-    pub use ty::{RustType, RustTypeDefs, UserDefinedDefs};
+    pub use enum_::EnumDefs;
+    pub use ffi::{FfiCallback, FfiFunction, FfiInterface, FfiLib, FfiStatic, FfiType};
+    pub use file::{CodegenOpts, RenameRule, TsModule};
+    pub use fn_::{catch_panic, FunctionDefs, TsMethod};
+    pub use intern::InternDefs;
+    pub use static_::ConstDefs;
+    pub use struct_::StructDefs;
+    pub use ty::{ErrorDefs, RustType, RustTypeDefs, RustTypeList, UserDefinedDefs};
     pub use util::TsFormat;
 }
 
 #[cfg(feature = "cli")]
-pub use deno::{CodegenOpts, TsModule};
-pub use rust::{File, Marker};
+pub use deno::{CodegenOpts, RenameRule, TsModule};
+pub use rust::{EnumTable, File, InternTable, Marker, StructTable};
@@ -1,5 +1,7 @@
+use syn::parse::Parser;
+
 use crate::rust::util::*;
-use crate::rust::Item;
+use crate::rust::{EnumTable, InternTable, Item, ItemImpl, ItemStruct, StructTable};
 
 /* -------------------------------------------------------------------------- */
 
@@ -14,6 +16,14 @@ use crate::rust::Item;
 pub enum Marker {
     DenoBindgen, // marks a deno bindgen item. automatically inserted by the item macro
     NonBlocking, /* marks a function as non-blocking */
+    Cancellable, /* passes a cancellation token to a non-blocking function */
+    JsString,    /* passes `&str` and `String` values as javascript strings */
+    Get,         /* generates a getter for a struct field */
+    Set,         /* generates a setter for a struct field */
+    Constructor, /* maps a static method returning `Self` to the class constructor */
+    Getter,      /* maps a method taking `&self` to a class `get` accessor */
+    Setter,      /* maps a method taking `&mut self` and a value to a class `set` accessor */
+    Rename(String), /* overrides the name of an item or member in the generated bindings */
 
                  /* [!TODO] support for translating member visibility https://www.typescriptlang.org/docs/handbook/2/classes.html#member-visibility
                   * interpret visibility of rust functions and interpolate as class visibility
@@ -24,31 +34,162 @@ pub enum Marker {
 // support custom inert attributes rfc
 // https://github.com/rust-lang/rust/issues/66079
 
+impl Marker {
+    /// The inert form of this marker, read by the cli from the expanded source
+    pub fn to_inert(&self) -> TokenStream {
+        let rename;
+        let value = match self {
+            Self::DenoBindgen => "deno_bindgen",
+            Self::NonBlocking => "deno_bindgen_non_blocking",
+            Self::Cancellable => "deno_bindgen_cancellable",
+            Self::JsString => "deno_bindgen_js_string",
+            Self::Get => "deno_bindgen_get",
+            Self::Set => "deno_bindgen_set",
+            Self::Constructor => "deno_bindgen_constructor",
+            Self::Getter => "deno_bindgen_getter",
+            Self::Setter => "deno_bindgen_setter",
+            Self::Rename(name) => {
+                rename = format!("{}{name}", Self::RENAME_PREFIX);
+                &rename
+            },
+        };
+        quote! { #[cfg_attr(not(deno_bindgen), doc = #value)] }
+    }
+
+    /// Parses an argument of the `#[non_blocking(...)]` attribute into its
+    /// marker
+    fn from_non_blocking_arg(meta: &syn::meta::ParseNestedMeta) -> Result<Self> {
+        if meta.path.is_ident("cancellable") {
+            Ok(Self::Cancellable)
+        } else {
+            Err(meta.error("unknown argument. expected `cancellable`"))
+        }
+    }
+
+    /// Parses a `rename = "name"` argument into its marker. The name must be a
+    /// valid javascript identifier
+    fn from_rename_arg(meta: &syn::meta::ParseNestedMeta) -> Result<Self> {
+        let lit_str = meta.value()?.parse::<LitStr>()?;
+        let name = lit_str.value();
+        // `$` is left out, as it is not valid in the identifiers of the token
+        // streams the bindings are printed from
+        let is_ident_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        if !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(is_ident_char)
+        {
+            Ok(Self::Rename(name))
+        } else {
+            Err(Error::new(
+                lit_str.span(),
+                "expected a valid javascript identifier, made of ascii letters, digits and `_`",
+            ))
+        }
+    }
+
+    /// Prefix of the inert `rename` marker, followed by the new name
+    const RENAME_PREFIX: &'static str = "deno_bindgen_rename:";
+
+    /// Set by the cli to the name of the crate whose functions all have the
+    /// `js_string` marker. Other crates in the build are left untouched
+    pub const JS_STRING_ENV: &'static str = "DENO_BINDGEN2_JS_STRING";
+}
+
 #[cfg(feature = "macro")]
 impl Marker {
-    pub fn deno_bindgen(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-        let input = TokenStream::from(input);
+    pub fn deno_bindgen(
+        attr: proc_macro2::TokenStream,
+        input: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
         let mut item: Item = match syn::parse2(input.clone()) {
             Ok(item) => item,
-            Err(err) => return err.to_compile_error().into(),
+            Err(err) => return err.to_compile_error(),
+        };
+        // item markers, i.e. `#[deno_bindgen(rename = "name")]`
+        let mut markers = vec![Self::DenoBindgen];
+        let parser = syn::meta::parser(|meta| {
+            if !meta.path.is_ident("rename") {
+                return Err(meta.error("unknown argument. expected `rename`"));
+            }
+            if let Item::Impl(_) | Item::Mod(_) = &item {
+                return Err(meta.error(
+                    "`rename` is not supported on impl blocks and modules. rename the class on \
+                     its type, or the methods with `#[deno_bindgen(rename = \"name\")]`",
+                ));
+            }
+            markers.push(Self::from_rename_arg(&meta)?);
+            Ok(())
+        });
+        if let Err(err) = parser.parse2(attr) {
+            return err.to_compile_error();
+        }
+        // interned types assigned to this item by the cli
+        let interned = InternTable::from_env().owned_by(&item);
+        if Self::js_string_from_env() {
+            item.push_marker(&Self::JsString);
+        }
+        if let Err(err) = item.resolve_types(&StructTable::from_env(), &EnumTable::from_env()) {
+            return err.to_compile_error();
+        }
+        // field and method markers are not valid attributes, so they are made
+        // inert
+        let input = match &item {
+            Item::Struct(_) => ItemStruct::inert_field_markers(input),
+            Item::Impl(_) => ItemImpl::inert_method_markers(input),
+            _ => Ok(input),
+        };
+        let input = match input {
+            Ok(input) => input,
+            Err(err) => return err.to_compile_error(),
         };
         item.transform();
+        let markers = markers.iter().map(Self::to_inert);
         quote! {
-            #[cfg_attr(not(deno_bindgen), doc = "deno_bindgen")]
+            #(#markers)*
             #input
             #item
+            #(#interned)*
+        }
+    }
+
+    pub fn non_blocking(
+        attr: proc_macro::TokenStream,
+        input: proc_macro::TokenStream,
+    ) -> proc_macro::TokenStream {
+        let input = TokenStream::from(input);
+        let mut markers = vec![Self::NonBlocking];
+        let parser = syn::meta::parser(|meta| {
+            markers.push(Self::from_non_blocking_arg(&meta)?);
+            Ok(())
+        });
+        if let Err(err) = syn::parse::Parser::parse(parser, attr) {
+            return err.to_compile_error().into();
+        }
+        let markers = markers.iter().map(Self::to_inert);
+        quote! {
+            #(#markers)*
+            #input
         }
         .into()
     }
 
-    pub fn non_blocking(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    pub fn js_string(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         let input = TokenStream::from(input);
         quote! {
-            #[cfg_attr(not(deno_bindgen), doc = "deno_bindgen_non_blocking")]
+            #[cfg_attr(not(deno_bindgen), doc = "deno_bindgen_js_string")]
             #input
         }
         .into()
     }
+
+    /// Whether the cli enabled the `js_string` marker for the crate being
+    /// compiled
+    fn js_string_from_env() -> bool {
+        let Ok(crate_name) = env_var(Self::JS_STRING_ENV) else {
+            return false;
+        };
+        env_var("CARGO_CRATE_NAME").is_ok_and(|name| name == crate_name.replace('-', "_"))
+    }
 }
 
 /* -------------------------------------------------------------------------- */
@@ -63,12 +204,23 @@ pub struct Meta {
 
 impl TryFrom<&Meta> for Marker {
     fn try_from(value: &Meta) -> Result<Self> {
-        match value.lit_str.value().as_str() {
+        let value_str = value.lit_str.value();
+        if let Some(name) = value_str.strip_prefix(Self::RENAME_PREFIX) {
+            return Ok(Self::Rename(name.to_string()));
+        }
+        match value_str.as_str() {
             "deno_bindgen" => Ok(Self::DenoBindgen),
             "deno_bindgen_non_blocking" => Ok(Self::NonBlocking),
+            "deno_bindgen_cancellable" => Ok(Self::Cancellable),
+            "deno_bindgen_js_string" => Ok(Self::JsString),
+            "deno_bindgen_get" => Ok(Self::Get),
+            "deno_bindgen_set" => Ok(Self::Set),
+            "deno_bindgen_constructor" => Ok(Self::Constructor),
+            "deno_bindgen_getter" => Ok(Self::Getter),
+            "deno_bindgen_setter" => Ok(Self::Setter),
             _ => Err(Error::new(
                 value.lit_str.span(),
-                "unknown value. expected one of `deno_bindgen`, `deno_bindgen_non_blocking`, `deno_bindgen_constructor`"
+                "unknown value. expected one of `deno_bindgen`, `deno_bindgen_non_blocking`, `deno_bindgen_cancellable`, `deno_bindgen_js_string`, `deno_bindgen_get`, `deno_bindgen_set`, `deno_bindgen_constructor`, `deno_bindgen_getter`, `deno_bindgen_setter`, `deno_bindgen_rename:<name>`"
             )),
         }
     }
@@ -83,7 +235,7 @@ impl Parse for Meta {
         if input.is_empty() {
             let key_str = key.to_string();
             match key_str.as_str() {
-                "non_blocking" => {
+                "non_blocking" | "js_string" => {
                     let lit_str = LitStr::new(
                         format!("deno_bindgen_{key_str}").as_str(),
                         Span::mixed_site(),
@@ -116,10 +268,13 @@ impl Parse for Meta {
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Attribute {
-    pub markers: Vec<Marker>,
-    pub meta:    Vec<Meta>,
-    // pub doc: Vec<String>, // [!TODO] support for documentation in code, with auto-generated docs
-    // by the tool
+    pub markers:    Vec<Marker>,
+    /// lines of the doc comments of the item, with their leading space
+    pub doc:        Vec<String>,
+    /// note of a `#[deprecated]` attribute, empty if it has none
+    pub deprecated: Option<String>,
+    /// representation hints of a `#[repr(...)]` attribute, i.e. `C` or `u8`
+    pub repr:       Vec<Ident>,
 }
 
 impl Attribute {
@@ -127,22 +282,64 @@ impl Attribute {
     pub fn has_deno_bindgen(&self) -> bool {
         self.markers
             .iter()
-            .find(|marker| match marker {
-                Marker::DenoBindgen => true,
-                _ => false,
-            })
-            .is_some()
+            .any(|marker| matches!(marker, Marker::DenoBindgen))
     }
 
     /// checks if this attribute contains the `non_blocking` marker
     pub fn has_non_blocking(&self) -> bool {
         self.markers
             .iter()
-            .find(|marker| match marker {
-                Marker::NonBlocking => true,
-                _ => false,
-            })
-            .is_some()
+            .any(|marker| matches!(marker, Marker::NonBlocking))
+    }
+
+    /// checks if this attribute contains the `cancellable` marker
+    pub fn has_cancellable(&self) -> bool {
+        self.markers.contains(&Marker::Cancellable)
+    }
+
+    /// checks if this attribute contains the `js_string` marker
+    pub fn has_js_string(&self) -> bool {
+        self.markers
+            .iter()
+            .any(|marker| matches!(marker, Marker::JsString))
+    }
+
+    /// checks if this attribute contains the `get` marker
+    pub fn has_get(&self) -> bool {
+        self.markers.contains(&Marker::Get)
+    }
+
+    /// checks if this attribute contains the `set` marker
+    pub fn has_set(&self) -> bool {
+        self.markers.contains(&Marker::Set)
+    }
+
+    /// checks if this attribute contains the `constructor` marker
+    pub fn has_constructor(&self) -> bool {
+        self.markers.contains(&Marker::Constructor)
+    }
+
+    /// checks if this attribute contains the `getter` marker
+    pub fn has_getter(&self) -> bool {
+        self.markers.contains(&Marker::Getter)
+    }
+
+    /// checks if this attribute contains the `setter` marker
+    pub fn has_setter(&self) -> bool {
+        self.markers.contains(&Marker::Setter)
+    }
+
+    /// the name given by a `rename` marker, if any
+    pub fn rename(&self) -> Option<&str> {
+        self.markers.iter().find_map(|marker| match marker {
+            Marker::Rename(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// checks if this attribute contains the given `#[repr(...)]` hint
+    pub fn has_repr(&self, hint: &str) -> bool {
+        self.repr.iter().any(|repr| repr == hint)
     }
 }
 
@@ -166,17 +363,137 @@ impl Attribute {
                 if let Ok(marker) = Marker::try_from(&meta) {
                     self.markers.push(marker);
                 } else {
-                    self.meta.push(meta);
+                    self.doc.push(meta.lit_str.value());
                 }
             } else {
                 // content should have been exhausted by doc_meta parser
-                content.parse::<syn::Meta>()?;
+                let meta = content.parse::<syn::Meta>()?;
+                if meta.path().is_ident("deprecated") {
+                    self.parse_deprecated(&meta)?;
+                } else if let syn::Meta::List(list) = &meta {
+                    if list.path.is_ident("repr") {
+                        self.parse_repr(list)?;
+                    } else if list.path.is_ident("deno_bindgen") {
+                        self.parse_markers(list)?;
+                    } else if list.path.is_ident("non_blocking") {
+                        self.parse_non_blocking(list)?;
+                    }
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Parses the note of `#[deprecated]`, `#[deprecated = "note"]` and
+    /// `#[deprecated(since = "1.0", note = "note")]` attributes
+    fn parse_deprecated(&mut self, meta: &syn::Meta) -> Result<()> {
+        let mut note = String::new();
+        match meta {
+            syn::Meta::Path(_) => (),
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit_str),
+                        ..
+                    }),
+                ..
+            }) => note = lit_str.value(),
+            syn::Meta::NameValue(name_value) => {
+                return Err(Error::new(
+                    name_value.value.span(),
+                    "expected a string literal",
+                ));
+            },
+            syn::Meta::List(list) => list.parse_nested_meta(|meta| {
+                let value = meta.value()?.parse::<LitStr>()?;
+                if meta.path.is_ident("note") {
+                    note = value.value();
+                }
+                Ok(())
+            })?,
+        }
+        self.deprecated = Some(note);
+        Ok(())
+    }
+
+    fn parse_repr(&mut self, list: &syn::MetaList) -> Result<()> {
+        list.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                self.repr.push(ident.clone());
+            }
+            // discard the arguments of hints like `align(8)`
+            if meta.input.peek(Paren) {
+                let content;
+                parenthesized!(content in meta.input);
+                content.parse::<TokenStream>()?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Parses the live `#[deno_bindgen(get, set)]` markers of a struct field
+    /// and the `#[deno_bindgen(constructor, getter, setter)]` markers of a
+    /// method. Both accept `rename = "name"`
+    fn parse_markers(&mut self, list: &syn::MetaList) -> Result<()> {
+        list.parse_nested_meta(|meta| {
+            if meta.path.is_ident("get") {
+                self.markers.push(Marker::Get);
+            } else if meta.path.is_ident("set") {
+                self.markers.push(Marker::Set);
+            } else if meta.path.is_ident("constructor") {
+                self.markers.push(Marker::Constructor);
+            } else if meta.path.is_ident("getter") {
+                self.markers.push(Marker::Getter);
+            } else if meta.path.is_ident("setter") {
+                self.markers.push(Marker::Setter);
+            } else if meta.path.is_ident("rename") {
+                self.markers.push(Marker::from_rename_arg(&meta)?);
+            } else {
+                return Err(meta.error(
+                    "unknown marker. expected `get`, `set`, `constructor`, `getter`, `setter` or \
+                     `rename`",
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    /// Replaces the live `#[deno_bindgen(...)]` markers of a field or method
+    /// with their inert form, as they are not valid attributes on their own
+    pub fn inert_markers(attrs: &mut Vec<syn::Attribute>) -> Result<()> {
+        let mut markers = Vec::new();
+        let mut err = None;
+        attrs.retain(|attr| {
+            if !attr.path().is_ident("deno_bindgen") {
+                return true;
+            }
+            let mut live_attr = Attribute::default();
+            match (|input: ParseStream| live_attr.parse_outer(input)).parse2(attr.to_token_stream())
+            {
+                Ok(()) => markers.append(&mut live_attr.markers),
+                Err(error) => err = Some(error),
+            }
+            false
+        });
+        if let Some(err) = err {
+            return Err(err);
+        }
+        for marker in markers {
+            attrs.extend(syn::Attribute::parse_outer.parse2(marker.to_inert())?);
+        }
+        Ok(())
+    }
+
+    /// Parses the live `#[non_blocking(cancellable)]` attribute of a method
+    fn parse_non_blocking(&mut self, list: &syn::MetaList) -> Result<()> {
+        self.markers.push(Marker::NonBlocking);
+        list.parse_nested_meta(|meta| {
+            self.markers.push(Marker::from_non_blocking_arg(&meta)?);
+            Ok(())
+        })
+    }
+
     pub fn parse_inner(&mut self, input: ParseStream) -> Result<()> {
         while input.peek(Token![#]) && input.peek2(Token![!]) {
             input.parse::<Token![#]>()?;
@@ -187,7 +504,7 @@ impl Attribute {
             let fork = content.fork();
             if let Ok(meta) = fork.parse::<Meta>() {
                 content.advance_to(&fork);
-                self.meta.push(meta);
+                self.doc.push(meta.lit_str.value());
             } else {
                 content.parse::<syn::Meta>()?;
             }
@@ -259,6 +576,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_repr() {
+        let attr = parse_quote!(Attribute,
+            #[repr(C, align(8))]
+            #[derive(Clone)]
+        );
+        dbg_assert!(attr.repr, vec![format_ident!("C"), format_ident!("align")]);
+        dbg_assert!(attr.has_repr("C"), true);
+    }
+
+    #[test]
+    fn test_field_markers() {
+        let attr = parse_quote!(Attribute,
+            #[deno_bindgen(get, set)]
+        );
+        dbg_assert!(attr.markers, vec![Marker::Get, Marker::Set]);
+
+        let attr = parse_quote!(Attribute,
+            #[doc = "deno_bindgen_get"]
+        );
+        dbg_assert!(attr.has_get(), true);
+        dbg_assert!(attr.has_set(), false);
+    }
+
+    #[test]
+    fn test_rename() {
+        let attr = parse_quote!(Attribute,
+            #[deno_bindgen(getter, rename = "fooBar")]
+        );
+        dbg_assert!(attr.markers, vec![
+            Marker::Getter,
+            Marker::Rename("fooBar".to_string())
+        ]);
+
+        let attr = parse_quote!(Attribute,
+            #[doc = "deno_bindgen_rename:fooBar"]
+        );
+        dbg_assert!(attr.rename(), Some("fooBar"));
+        dbg_assert!(
+            Marker::Rename("fooBar".to_string()).to_inert().to_string(),
+            quote! { #[cfg_attr(not(deno_bindgen), doc = "deno_bindgen_rename:fooBar")] }
+                .to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rename_invalid() {
+        parse_quote!(Attribute,
+            #[deno_bindgen(rename = "0foo")]
+        );
+    }
+
+    #[test]
+    fn test_doc() {
+        let attr = parse_quote!(Attribute,
+            /// Adds two numbers
+            ///
+            /// Wraps on overflow
+            #[doc = "deno_bindgen"]
+        );
+        dbg_assert!(attr.doc, vec![
+            " Adds two numbers",
+            "",
+            " Wraps on overflow"
+        ]);
+        dbg_assert!(attr.has_deno_bindgen(), true);
+    }
+
+    #[test]
+    fn test_deprecated() {
+        let attr = parse_quote!(Attribute, #[deprecated]);
+        dbg_assert!(attr.deprecated, Some(String::new()));
+
+        let attr = parse_quote!(Attribute, #[deprecated = "use `add` instead"]);
+        dbg_assert!(attr.deprecated, Some("use `add` instead".to_string()));
+
+        let attr = parse_quote!(Attribute,
+            #[deprecated(since = "1.0.0", note = "use `add` instead")]
+        );
+        dbg_assert!(attr.deprecated, Some("use `add` instead".to_string()));
+    }
+
     #[test]
     fn test_live_attr() {
         dbg_quote!(Attribute,
@@ -0,0 +1,684 @@
+use std::collections::BTreeMap;
+
+use syn::parse::Parser;
+
+use crate::rust::util::*;
+use crate::rust::{
+    assoc_fn, export_name, Association, Attribute, Field, Item, ItemFn, Type, TypeNumeric,
+};
+
+// MARK: api
+
+/// An enum exported by the crate
+///
+/// Fieldless enums with an integer `#[repr(...)]` are passed as that integer
+/// and exported as a typescript enum. Other enums are passed as opaque pointers
+/// and exported as a class which converts to and from a discriminated union
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemEnum {
+    pub attr:     Attribute,
+    pub ident:    Ident,
+    pub repr:     Option<TypeNumeric>,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variant {
+    pub ident:        Ident,
+    pub discriminant: i128,
+    /// fields of a struct or tuple variant, empty for a unit variant
+    pub fields:       Vec<Field>,
+}
+
+impl Parse for ItemEnum {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut attr = Attribute::default();
+        attr.parse_outer(input)?;
+        input.parse::<Visibility>()?;
+
+        input.parse::<Token![enum]>()?;
+        Self::parse_remaining(input, attr)
+    }
+}
+
+impl ItemEnum {
+    pub fn parse_remaining(input: ParseStream, attr: Attribute) -> Result<Self> {
+        // continued after parsing the `enum` token
+        let ident: Ident = input.parse()?;
+
+        if let Some(lt_token) = input.parse::<Option<Token![<]>>()? {
+            return Err(Error::new(
+                lt_token.span(),
+                "generic parameters are not supported",
+            ));
+        }
+
+        if let Some(where_) = input.parse::<Option<Token![where]>>()? {
+            return Err(Error::new(
+                where_.span(),
+                "generic parameters and where clauses are not supported",
+            ));
+        }
+
+        let repr = Self::parse_repr(&attr);
+
+        let content;
+        braced!(content in input);
+
+        let mut variants = Vec::new();
+        let mut next = 0;
+        while !content.is_empty() {
+            // discards any attribute if any
+            content.call(syn::Attribute::parse_outer)?;
+            let ident: Ident = content.parse()?;
+            let fields = if content.peek(Brace) {
+                syn::FieldsNamed::parse(&content)?.named
+            } else if content.peek(Paren) {
+                syn::FieldsUnnamed::parse(&content)?.unnamed
+            } else {
+                Default::default()
+            };
+
+            let parse_type = |input: ParseStream| Type::parse(input, None);
+            let fields = fields
+                .into_iter()
+                .map(|field| {
+                    Ok(Field {
+                        attr:  Attribute::default(),
+                        ident: field.ident,
+                        ty:    parse_type.parse2(field.ty.into_token_stream())?,
+                    })
+                })
+                .collect::<Result<_>>()?;
+
+            let discriminant = if content.parse::<Option<Token![=]>>()?.is_some() {
+                Self::parse_discriminant(&content)?
+            } else {
+                next
+            };
+            next = discriminant + 1;
+            variants.push(Variant {
+                ident,
+                discriminant,
+                fields,
+            });
+
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<Token![,]>()?;
+        }
+
+        if variants.is_empty() {
+            return Err(Error::new(
+                ident.span(),
+                "enums without variants are not supported",
+            ));
+        }
+
+        Ok(Self {
+            attr,
+            ident,
+            repr,
+            variants,
+        })
+    }
+
+    /// The integer type of the enum's `#[repr(...)]`. `#[repr(C)]` enums are
+    /// passed as a C `int`
+    fn parse_repr(attr: &Attribute) -> Option<TypeNumeric> {
+        let parse_type = |input: ParseStream| Type::parse(input, None);
+        for hint in &attr.repr {
+            if hint == "C" {
+                return Some(TypeNumeric::I32);
+            }
+            match parse_type.parse2(hint.to_token_stream()) {
+                Ok(Type::Numeric(TypeNumeric::F32 | TypeNumeric::F64)) | Err(_) => (),
+                Ok(Type::Numeric(repr)) => return Some(repr),
+                Ok(_) => (),
+            }
+        }
+        None
+    }
+
+    fn parse_discriminant(input: ParseStream) -> Result<i128> {
+        let expr = input.parse::<syn::Expr>()?;
+        match &expr {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(int),
+                ..
+            }) => int.base10_parse(),
+            syn::Expr::Unary(syn::ExprUnary {
+                op: syn::UnOp::Neg(_),
+                expr: inner,
+                ..
+            }) => match &**inner {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(int),
+                    ..
+                }) => Ok(-int.base10_parse::<i128>()?),
+                _ => Err(Error::new(expr.span(), "unsupported discriminant\nnote: constant expressions cannot be evaluated. please provide an integer literal")),
+            },
+            _ => Err(Error::new(expr.span(), "unsupported discriminant\nnote: constant expressions cannot be evaluated. please provide an integer literal")),
+        }
+    }
+
+    /// The integer an enum is passed as, if it is fieldless and has an integer
+    /// representation. Other enums are passed as opaque pointers
+    pub fn c_like_repr(&self) -> Option<TypeNumeric> {
+        self.repr.filter(|_| {
+            self.variants
+                .iter()
+                .all(|variant| variant.fields.is_empty())
+        })
+    }
+
+    /// The helper functions of an enum passed as an opaque pointer, with their
+    /// name in typescript. The fields of a tuple variant are named `0`, `1`,
+    /// ...
+    ///
+    /// - `discriminant(&self) -> u32`, the index of the current variant
+    /// - `new_V(A, B) -> Self` for every variant, named `V`
+    /// - `get_V_x(&self) -> X` for copyable fields, `-> &X` otherwise. Panics
+    ///   if the enum is not a `V`
+    pub fn helpers(&self) -> Vec<(Ident, ItemFn)> {
+        let self_ty = Type::UserDefined(self.ident.clone());
+        let method = |ident: &str, output: Type| ItemFn {
+            assoc: Some(Association::Instance),
+            ..assoc_fn(
+                &self.ident,
+                ident,
+                vec![Type::Ref(Box::new(self_ty.clone()))],
+                output,
+            )
+        };
+
+        let mut items = vec![(
+            format_ident!("discriminant"),
+            method("discriminant", Type::Numeric(TypeNumeric::U32)),
+        )];
+        for variant in &self.variants {
+            let inputs = variant
+                .fields
+                .iter()
+                .map(|field| field.ty.clone())
+                .collect();
+            items.push((
+                variant.ident.clone(),
+                assoc_fn(
+                    &self.ident,
+                    &format!("new_{}", variant.ident),
+                    inputs,
+                    self_ty.clone(),
+                ),
+            ));
+            for (i, field) in variant.fields.iter().enumerate() {
+                let name = format!("get_{}_{}", variant.ident, field_name(field, i));
+                let output = if field.ty.is_copy() {
+                    field.ty.clone()
+                } else {
+                    Type::Ref(Box::new(field.ty.clone()))
+                };
+                items.push((format_ident!("{name}"), method(&name, output)));
+            }
+        }
+        items
+    }
+
+    /// The rust implementation of the functions returned by [`Self::helpers`]
+    fn impl_helpers(&self) -> TokenStream {
+        let ident = &self.ident;
+        let patterns: Vec<TokenStream> = self
+            .variants
+            .iter()
+            .map(|variant| {
+                let variant = &variant.ident;
+                quote! { Self::#variant { .. } }
+            })
+            .collect();
+        let indices = (0..self.variants.len() as u32).map(proc_macro2::Literal::u32_unsuffixed);
+
+        let mut items = quote! {
+            pub fn discriminant(&self) -> u32 {
+                match self {
+                    #(#patterns => #indices,)*
+                }
+            }
+        };
+        for variant in &self.variants {
+            let variant_ident = &variant.ident;
+            let members: Vec<syn::Member> = variant
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| match &field.ident {
+                    Some(ident) => syn::Member::Named(ident.clone()),
+                    None => syn::Member::Unnamed(syn::Index::from(i)),
+                })
+                .collect();
+            let args: Vec<Ident> = (0..members.len())
+                .map(|i| format_ident!("arg_{i}"))
+                .collect();
+            let types = variant.fields.iter().map(|field| &field.ty);
+
+            let new = format_ident!("new_{variant_ident}");
+            items.extend(quote! {
+                pub fn #new(#(#args: #types),*) -> Self {
+                    Self::#variant_ident { #(#members: #args),* }
+                }
+            });
+
+            let message = format!("`{ident}` is not a `{variant_ident}`");
+            for (i, (field, member)) in variant.fields.iter().zip(&members).enumerate() {
+                let get = format_ident!("get_{variant_ident}_{}", field_name(field, i));
+                let ty = &field.ty;
+                let (output, value) = if ty.is_copy() {
+                    (quote! { #ty }, quote! { *value })
+                } else {
+                    (quote! { &#ty }, quote! { value })
+                };
+                items.extend(quote! {
+                    pub fn #get(&self) -> #output {
+                        match self {
+                            Self::#variant_ident { #member: value, .. } => #value,
+                            _ => std::panic!(#message),
+                        }
+                    }
+                });
+            }
+        }
+        items
+    }
+}
+
+/// The name of a field in the rust helper functions, i.e. `x` or `0`
+fn field_name(field: &Field, index: usize) -> String {
+    match &field.ident {
+        Some(ident) => ident.to_string(),
+        None => index.to_string(),
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: enum table
+
+/// The C-like enums of a crate and their integer representation. Function
+/// signatures only name these types, so the table is built by the cli from the
+/// expanded source code and passed to the macro through the
+/// [`EnumTable::ENV`] environment variable
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EnumTable {
+    store: BTreeMap<String, TypeNumeric>,
+}
+
+impl EnumTable {
+    pub const ENV: &'static str = "DENO_BINDGEN2_ENUMS";
+
+    /// Builds the enum table of a crate from its `deno_bindgen` items
+    pub fn collect(items: &[Item]) -> Self {
+        let mut table = Self::default();
+        table.collect_items(items);
+        table
+    }
+
+    fn collect_items(&mut self, items: &[Item]) {
+        for item in items {
+            match item {
+                Item::Enum(item_enum) => {
+                    if let Some(repr) = item_enum.c_like_repr() {
+                        self.store.insert(item_enum.ident.to_string(), repr);
+                    }
+                },
+                Item::Mod(item_mod) => self.collect_items(&item_mod.items),
+                _ => (),
+            }
+        }
+    }
+
+    /// Reads the table passed by the cli. The table is empty when the crate is
+    /// not being built by the cli
+    #[cfg(feature = "macro")]
+    pub fn from_env() -> Self {
+        match env_var(Self::ENV) {
+            Ok(table) => Self::parse_env(&table),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parses the table from its environment variable representation, one
+    /// `enum_name repr` pair per line
+    pub fn parse_env(table: &str) -> Self {
+        let parse_type = |input: ParseStream| Type::parse(input, None);
+        let store = table
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .filter_map(|(ident, repr)| match parse_type.parse_str(repr) {
+                Ok(Type::Numeric(repr)) => Some((ident.to_string(), repr)),
+                _ => None,
+            })
+            .collect();
+        Self { store }
+    }
+
+    pub fn to_env(&self) -> String {
+        self.store
+            .iter()
+            .map(|(ident, repr)| format!("{ident} {}\n", Type::Numeric(*repr).mangle()))
+            .collect()
+    }
+
+    pub fn get(&self, ident: &Ident) -> Option<TypeNumeric> {
+        self.store.get(&ident.to_string()).copied()
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: parse tests
+
+#[cfg(test)]
+mod parse_tests {
+    use super::*;
+    use crate::rust::File;
+
+    #[test]
+    fn test_parse_enum() {
+        dbg_assert!(
+            parse_quote!(
+                ItemEnum,
+                #[repr(u8)]
+                enum Mode {
+                    A,
+                    #[allow(dead_code)]
+                    B = 4,
+                    C,
+                    D = -1,
+                }
+            ),
+            ItemEnum {
+                attr:     parse_quote!(
+                    ItemEnum,
+                    #[repr(u8)]
+                    enum Mode {
+                        A,
+                    }
+                )
+                .attr,
+                ident:    format_ident!("Mode"),
+                repr:     Some(TypeNumeric::U8),
+                variants: vec![
+                    Variant {
+                        ident:        format_ident!("A"),
+                        discriminant: 0,
+                        fields:       Vec::new(),
+                    },
+                    Variant {
+                        ident:        format_ident!("B"),
+                        discriminant: 4,
+                        fields:       Vec::new(),
+                    },
+                    Variant {
+                        ident:        format_ident!("C"),
+                        discriminant: 5,
+                        fields:       Vec::new(),
+                    },
+                    Variant {
+                        ident:        format_ident!("D"),
+                        discriminant: -1,
+                        fields:       Vec::new(),
+                    },
+                ],
+            }
+        );
+
+        let item_enum = parse_quote!(
+            ItemEnum,
+            #[repr(C)]
+            enum Mode {
+                A,
+            }
+        );
+        dbg_assert!(item_enum.c_like_repr(), Some(TypeNumeric::I32));
+    }
+
+    #[test]
+    fn test_parse_enum_with_fields() {
+        let item_enum = parse_quote!(
+            ItemEnum,
+            enum Shape {
+                Circle { r: f64 },
+                Pair(u8, String),
+                Empty,
+            }
+        );
+        dbg_assert!(item_enum.c_like_repr(), None);
+        dbg_assert!(item_enum.variants[0].fields, vec![Field {
+            attr:  Attribute::default(),
+            ident: Some(format_ident!("r")),
+            ty:    Type::Numeric(TypeNumeric::F64),
+        }]);
+        dbg_assert!(item_enum.variants[1].fields[1].ty, Type::String);
+        dbg_assert!(item_enum.variants[2].fields, Vec::new());
+
+        let helpers: Vec<String> = item_enum
+            .helpers()
+            .iter()
+            .map(|(name, item)| format!("{name} {}", item.shim_ident()))
+            .collect();
+        dbg_assert!(helpers, vec![
+            "discriminant __Shape__discriminant",
+            "Circle __Shape__new_Circle",
+            "get_Circle_r __Shape__get_Circle_r",
+            "Pair __Shape__new_Pair",
+            "get_Pair_0 __Shape__get_Pair_0",
+            "get_Pair_1 __Shape__get_Pair_1",
+            "Empty __Shape__new_Empty",
+        ]);
+
+        // fieldless enums without an integer representation are passed as
+        // opaque pointers as well
+        let item_enum = parse_quote!(
+            ItemEnum,
+            enum Mode {
+                A,
+                B,
+            }
+        );
+        dbg_assert!(item_enum.c_like_repr(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_enum_with_generics() {
+        parse_quote!(
+            ItemEnum,
+            enum Shape<T> {
+                Value(T),
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_enum_with_const_expr() {
+        parse_quote!(
+            ItemEnum,
+            #[repr(u8)]
+            enum Mode {
+                A = 1 << 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_enum_table() {
+        let file = File::parse_str(
+            quote! {
+                #[doc = "deno_bindgen"]
+                #[repr(u8)]
+                enum Mode { A, B }
+
+                #[doc = "deno_bindgen"]
+                #[repr(i64)]
+                enum Level { Low = -1, High = 1 }
+
+                #[repr(u8)]
+                enum Ignored { A }
+
+                #[doc = "deno_bindgen"]
+                #[repr(u8)]
+                enum Shape { Circle { r: f64 } }
+            }
+            .to_string()
+            .as_str(),
+        );
+
+        let table = EnumTable::collect(&file.items);
+        dbg_assert!(table.to_env(), "Level i64\nMode u8\n");
+        dbg_assert!(EnumTable::parse_env(&table.to_env()), table);
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: print
+
+impl ToTokens for ItemEnum {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = &self.ident;
+        let Some(repr) = self.c_like_repr() else {
+            let drop = format_ident!("__{}__drop", self.ident);
+            let export_drop = export_name(&drop, false);
+            let impl_helpers = self.impl_helpers();
+            let mut helpers: Vec<ItemFn> =
+                self.helpers().into_iter().map(|(_, item)| item).collect();
+            for item in &mut helpers {
+                item.transform();
+            }
+            tokens.extend(quote! {
+                impl deno_bindgen2::DenoBindgen for #ident {}
+                #export_drop
+                extern "C" fn #drop (arg_0: *mut #ident) {
+                    std::mem::drop(Box::from(arg_0));
+                }
+                const _: () = {
+                    #[allow(non_snake_case, unreachable_patterns)]
+                    impl #ident {
+                        #impl_helpers
+                    }
+
+                    #(#helpers)*
+                };
+            });
+            return;
+        };
+        let repr = Type::Numeric(repr);
+        let variants = self.variants.iter().map(|variant| &variant.ident);
+        let discriminants = self
+            .variants
+            .iter()
+            .map(|variant| proc_macro2::Literal::i128_unsuffixed(variant.discriminant));
+        let message = format!("invalid discriminant `{{value}}` for `{ident}`");
+
+        tokens.extend(quote! {
+            impl deno_bindgen2::DenoBindgen for #ident {}
+            const _: () = {
+                impl #ident {
+                    #[doc(hidden)]
+                    pub fn __from_repr(value: #repr) -> Self {
+                        match value {
+                            #(#discriminants => Self::#variants,)*
+                            _ => std::panic!(#message),
+                        }
+                    }
+                }
+            };
+        });
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: print tests
+
+#[cfg(test)]
+mod print_tests {
+    use super::*;
+
+    #[test]
+    fn test_print_enum() {
+        let raw = parse_quote!(
+            ItemEnum,
+            #[repr(i8)]
+            enum Mode {
+                A = -1,
+                B,
+            }
+        );
+        let expected = quote! {
+            impl deno_bindgen2::DenoBindgen for Mode {}
+            const _: () = {
+                impl Mode {
+                    #[doc(hidden)]
+                    pub fn __from_repr(value: std::primitive::i8) -> Self {
+                        match value {
+                            -1 => Self::A,
+                            0 => Self::B,
+                            _ => std::panic!("invalid discriminant `{value}` for `Mode`"),
+                        }
+                    }
+                }
+            };
+        };
+        dbg_assert!(
+            crate::prettify!(raw.to_token_stream().to_string().as_str()),
+            crate::prettify!(expected.to_string().as_str())
+        );
+    }
+    #[test]
+    fn test_print_enum_with_fields() {
+        let raw = parse_quote!(
+            ItemEnum,
+            enum Shape {
+                Circle { r: f64 },
+                Named(String),
+            }
+        );
+        let impl_helpers = quote! {
+            pub fn discriminant(&self) -> u32 {
+                match self {
+                    Self::Circle { .. } => 0,
+                    Self::Named { .. } => 1,
+                }
+            }
+            pub fn new_Circle(arg_0: std::primitive::f64) -> Self {
+                Self::Circle { r: arg_0 }
+            }
+            pub fn get_Circle_r(&self) -> std::primitive::f64 {
+                match self {
+                    Self::Circle { r: value, .. } => *value,
+                    _ => std::panic!("`Shape` is not a `Circle`"),
+                }
+            }
+            pub fn new_Named(arg_0: std::string::String) -> Self {
+                Self::Named { 0: arg_0 }
+            }
+            pub fn get_Named_0(&self) -> &std::string::String {
+                match self {
+                    Self::Named { 0: value, .. } => value,
+                    _ => std::panic!("`Shape` is not a `Named`"),
+                }
+            }
+        };
+        let actual = raw.impl_helpers();
+        dbg_assert!(
+            crate::prettify!(quote!(impl Shape { #actual }).to_string().as_str()),
+            crate::prettify!(quote!(impl Shape { #impl_helpers }).to_string().as_str())
+        );
+        println!(
+            "{}",
+            crate::prettify!(raw.to_token_stream().to_string().as_str())
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::rust::util::*;
-use crate::rust::{Attribute, Type};
+use crate::rust::{Attribute, EnumTable, StructTable, Type, TypeDynFn, TypeNumeric};
 
 /* -------------------------------------------------------------------------- */
 
@@ -10,6 +10,7 @@ pub struct ItemFn {
     pub attr:    Attribute,
     pub vis:     Visibility,
     pub const_:  Option<Token![const]>,
+    pub async_:  Option<Token![async]>,
     pub unsafe_: Option<Token![unsafe]>,
     pub ident:   Ident,
     pub inputs:  Vec<Type>,
@@ -28,10 +29,33 @@ pub enum Association {
 
 #[derive(Clone, Debug, Default)]
 pub struct Block {
-    pub args:     Vec<Ident>,
-    pub in_stmts: Vec<TokenStream>,
-    pub out_stmt: Option<TokenStream>,
-    pub self_ty:  Option<Ident>,
+    pub args:        Vec<Ident>,
+    pub in_stmts:    Vec<TokenStream>,
+    pub out_stmt:    Option<TokenStream>,
+    /// trailing out-parameters of the shim, written to by `out_stmt`
+    pub out_args:    Vec<(Ident, Type)>,
+    /// length parameters of the shim, each following the buffer argument at
+    /// the given index
+    pub len_args:    Vec<(usize, Ident)>,
+    /// context parameters of the shim, following the callback argument of a
+    /// closure at the given index
+    pub ctx_args:    Vec<(usize, Ident, Type)>,
+    /// result type of an `async` function, resolved by the task its shim
+    /// spawns
+    pub task_output: Option<Type>,
+    /// parameter types of a `non_blocking` function, which are sent to the
+    /// thread the call runs on
+    pub send_args:   Vec<Type>,
+    pub self_ty:     Option<Ident>,
+    /// trait implemented by the self type, whose method is called as
+    /// `<T as Trait>::method`
+    pub trait_:      Option<syn::Path>,
+    /// accessor of an interned type, which is emitted once for the whole
+    /// crate and exported under the crate name rather than its module path
+    pub interned:    bool,
+    /// names of the parameters of the function, documented by the bindings.
+    /// `self` for a receiver and `_` for other patterns
+    pub params:      Vec<String>,
 }
 
 // MARK: parse
@@ -53,12 +77,7 @@ impl ItemFn {
         self_ty: Option<&Ident>,
     ) -> Result<Self> {
         let const_ = input.parse()?;
-        if let Some(async_) = input.parse::<Option<Token![async]>>()? {
-            return Err(Error::new(
-                async_.span,
-                "unsupported async function qualifier",
-            ));
-        }
+        let async_ = input.parse()?;
         let unsafe_ = input.parse()?;
 
         // TODO: should extern functions be restricted?
@@ -75,7 +94,7 @@ impl ItemFn {
             }
         }
         input.parse::<Token![fn]>()?;
-        Self::parse_remaining(input, attr, vis, self_ty, const_, unsafe_)
+        Self::parse_remaining(input, attr, vis, self_ty, const_, async_, unsafe_)
     }
 
     /// `parse_remaining` is used by the `item` parser since it has already
@@ -86,6 +105,7 @@ impl ItemFn {
         vis: Visibility,
         self_ty: Option<&Ident>,
         const_: Option<Token![const]>,
+        async_: Option<Token![async]>,
         unsafe_: Option<Token![unsafe]>,
     ) -> Result<Self> {
         // continued after parsing the `fn` token
@@ -101,6 +121,7 @@ impl ItemFn {
         let content;
         parenthesized!(content in input);
         let mut inputs = Vec::new();
+        let mut params = Vec::new();
 
         let mut assoc = if self_ty.is_some() {
             Some(Association::Static)
@@ -124,6 +145,7 @@ impl ItemFn {
                 ),
             });
             inputs.push(ty);
+            params.push(String::from("self"));
             // parse trailing comma
             content.parse::<Option<Token![,]>>()?;
         }
@@ -131,7 +153,10 @@ impl ItemFn {
         while !content.is_empty() {
             // discards any attribute if any
             content.call(syn::Attribute::parse_outer)?;
-            Pat::parse_single(&content)?;
+            params.push(match Pat::parse_single(&content)? {
+                Pat::Ident(pat) => pat.ident.to_string(),
+                _ => String::from("_"),
+            });
             content.parse::<Token![:]>()?;
             inputs.push(Type::parse(&content, self_ty)?);
             if content.is_empty() {
@@ -164,7 +189,92 @@ impl ItemFn {
         attr.parse_inner(&content)?;
         content.call(syn::Block::parse_within)?;
 
-        let mut block = Block::default();
+        // the future of an `async` function is spawned on the runtime and
+        // outlives the call, so it cannot borrow its arguments
+        if let Some(async_) = async_ {
+            if attr.has_non_blocking() {
+                return Err(Error::new(
+                    async_.span,
+                    "`async` functions already return a promise and cannot be marked `non_blocking`",
+                ));
+            }
+            let borrowed = |ty: &Type| matches!(ty, Type::Ref(_) | Type::RefMut(_));
+            if inputs.iter().any(|input| match input {
+                Type::Option(elem) => borrowed(elem),
+                input => borrowed(input),
+            }) {
+                return Err(Error::new(
+                    async_.span,
+                    "parameters of `async` functions must be owned\nnote: the future is spawned on a runtime and outlives the call",
+                ));
+            }
+        }
+
+        // the class constructor wraps the value returned by a static method,
+        // and cannot be awaited
+        if attr.has_constructor() {
+            let returns_self = match (&output, self_ty) {
+                (Type::UserDefined(ty), Some(self_ty)) => ty == self_ty,
+                _ => false,
+            };
+            if assoc != Some(Association::Static)
+                || !returns_self
+                || async_.is_some()
+                || attr.has_non_blocking()
+            {
+                return Err(Error::new(
+                    ident.span(),
+                    "a `constructor` must be a static method returning `Self`, and cannot be `async` or `non_blocking`",
+                ));
+            }
+        }
+
+        // accessors are read and assigned like a property of the instance, so
+        // they cannot be awaited
+        if attr.has_get() || attr.has_set() {
+            return Err(Error::new(
+                ident.span(),
+                "`get` and `set` are markers of struct fields. expected `getter` or `setter`",
+            ));
+        }
+        if attr.has_getter()
+            && (assoc != Some(Association::Instance)
+                || inputs.len() != 1
+                || output == Type::Void
+                || async_.is_some()
+                || attr.has_non_blocking())
+        {
+            return Err(Error::new(
+                ident.span(),
+                "a `getter` must take `&self` alone and return a value, and cannot be `async` or `non_blocking`",
+            ));
+        }
+        if attr.has_setter()
+            && (assoc != Some(Association::InstanceMut)
+                || inputs.len() != 2
+                || output != Type::Void
+                || async_.is_some()
+                || attr.has_non_blocking())
+        {
+            return Err(Error::new(
+                ident.span(),
+                "a `setter` must take `&mut self` and a value and return nothing, and cannot be `async` or `non_blocking`",
+            ));
+        }
+
+        // the token of a `cancellable` function is tripped by the caller
+        // through a companion symbol, in place of its last parameter
+        if attr.has_cancellable() && !inputs.last().is_some_and(Type::is_cancellation_token) {
+            return Err(Error::new(
+                ident.span(),
+                "`cancellable` functions must take a `&CancellationToken` as their last parameter",
+            ));
+        }
+
+        let mut block = Block {
+            params,
+            ..Default::default()
+        };
         if let Some(self_ty) = self_ty {
             block.self_ty = Some(self_ty.clone());
         }
@@ -173,6 +283,7 @@ impl ItemFn {
             attr,
             vis,
             const_,
+            async_,
             unsafe_,
             ident,
             inputs,
@@ -211,9 +322,46 @@ mod parse_tests {
     }
 
     #[test]
-    #[should_panic]
     fn test_async() {
-        dbg_quote!(ItemFn, async fn test_fn() {});
+        dbg_quote!(ItemFn, async fn test_fn(arg0: String) -> usize {});
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_async_borrowed() {
+        dbg_quote!(ItemFn, async fn test_fn(arg0: &str) {});
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_async_non_blocking() {
+        dbg_quote!(
+            ItemFn,
+            #[doc = "deno_bindgen_non_blocking"]
+            async fn test_fn() {}
+        );
+    }
+
+    #[test]
+    fn test_cancellable() {
+        let item_fn = parse_quote!(
+            ItemFn,
+            #[doc = "deno_bindgen_non_blocking"]
+            #[doc = "deno_bindgen_cancellable"]
+            fn test_fn(arg0: u32, token: &CancellationToken) -> u32 {}
+        );
+        dbg_assert!(item_fn.attr.has_cancellable(), true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cancellable_without_token() {
+        dbg_quote!(
+            ItemFn,
+            #[doc = "deno_bindgen_non_blocking"]
+            #[doc = "deno_bindgen_cancellable"]
+            fn test_fn(arg0: u32) {}
+        );
     }
 
     #[test]
@@ -344,30 +492,198 @@ mod parse_tests {
 // MARK: print
 
 impl ItemFn {
+    /// The name of the `extern "C"` shim that wraps this function
+    pub fn shim_ident(&self) -> Ident {
+        match &self.block.self_ty {
+            Some(self_ty) => format_ident!("__{}__{}", self_ty.to_string(), self.ident.to_string()),
+            None => format_ident!("__{}", self.ident.to_string()),
+        }
+    }
+
+    /// The name of the shim that takes the result of the task spawned by an
+    /// `async` function
+    pub fn await_ident(&self) -> Ident {
+        format_ident!("{}__await", self.shim_ident())
+    }
+
+    /// Collects the interned types used in this function's signature
+    pub fn collect_interned(&self, interned: &mut Vec<Type>) {
+        for input in &self.inputs {
+            input.collect_interned(interned);
+        }
+        self.output.collect_interned(interned);
+    }
+
+    /// Resolves the parameter and result types naming structs passed by value
+    /// or C-like enums
+    pub fn resolve_types(&mut self, structs: &StructTable, enums: &EnumTable) -> Result<()> {
+        for input in &mut self.inputs {
+            input.resolve_structs(structs)?;
+            input.resolve_enums(enums)?;
+        }
+        self.output.resolve_structs(structs)?;
+        self.output.resolve_enums(enums)
+    }
+
     pub fn transform(&mut self) {
+        let js_string = self.attr.has_js_string();
+        let non_blocking = self.attr.has_non_blocking();
         let ItemFn {
+            async_,
             inputs,
             output,
             block,
             ..
         } = self;
+        if async_.is_some() {
+            block.task_output = Some(output.clone());
+        }
+        // raw pointers are left to the user, and closures are wrappers built
+        // and called by the shim on the thread it runs on
+        if non_blocking {
+            block.send_args = inputs
+                .iter()
+                .filter(|input| {
+                    input.closure().is_none()
+                        && !matches!(input, Type::Ptr(_) | Type::PtrMut(_) | Type::FnPtr(_))
+                })
+                .cloned()
+                .collect();
+        }
         let Block {
             args,
             in_stmts,
             out_stmt,
+            out_args,
+            len_args,
+            ctx_args,
             ..
         } = block;
 
         for (i, input) in inputs.iter_mut().enumerate() {
             let ident = format_ident!("arg_{i}");
             match input {
+                // called back through an `extern "C"` function and a context
+                // pointer. boxed closures may outlive the call, so they also
+                // take a function releasing the context once dropped
+                _ if input.closure().is_some() => {
+                    let TypeDynFn {
+                        inputs: params,
+                        output: result,
+                        ..
+                    } = input.closure().unwrap().clone();
+                    let ctx = format_ident!("arg_{i}_ctx");
+                    let values: Vec<Ident> = (0..params.len())
+                        .map(|i| format_ident!("value_{i}"))
+                        .collect();
+                    let result = match *result {
+                        Type::Void => TokenStream::new(),
+                        result => quote! { -> #result },
+                    };
+                    let c_void = Type::Unsupported(syn::parse_quote!(std::ffi::c_void));
+                    ctx_args.push((i, ctx.clone(), Type::Ptr(Box::new(c_void.clone()))));
+
+                    in_stmts.push(match input {
+                        Type::Box(_) => {
+                            let release = format_ident!("arg_{i}_release");
+                            ctx_args.push((
+                                i,
+                                release.clone(),
+                                Type::FnPtr(syn::parse_quote!(extern "C" fn(*const #c_void))),
+                            ));
+                            quote! {
+                                let #ident = {
+                                    let #ctx = deno_bindgen2::RustCallback::new(#ctx, #release);
+                                    std::boxed::Box::new(move |#(#values: #params),*| {
+                                        #ident(#ctx.ctx(), #(#values),*)
+                                    })
+                                };
+                            }
+                        },
+                        // the context is captured as an integer to keep the
+                        // closure `Send` and `Sync`
+                        Type::Ref(_) => quote! {
+                            let #ident = {
+                                let #ctx = #ctx as std::primitive::usize;
+                                move |#(#values: #params),*| {
+                                    #ident(#ctx as *const #c_void, #(#values),*)
+                                }
+                            };
+                            let #ident = &#ident;
+                        },
+                        _ => quote! {
+                            let mut #ident = {
+                                let #ctx = #ctx as std::primitive::usize;
+                                move |#(#values: #params),*| {
+                                    #ident(#ctx as *const #c_void, #(#values),*)
+                                }
+                            };
+                            let #ident = &mut #ident;
+                        },
+                    });
+                    *input = Type::FnPtr(syn::parse_quote! {
+                        extern "C" fn(*const #c_void, #(#params),*) #result
+                    });
+                },
                 Type::Void
                 | Type::Numeric(_)
                 | Type::Bool
                 | Type::Char
                 | Type::Ptr(_)
                 | Type::PtrMut(_)
-                | Type::FnPtr(_) => (),
+                | Type::FnPtr(_)
+                | Type::Struct(_) => (),
+                // a struct passed by value is borrowed from the shim's copy
+                Type::Ref(elem) if matches!(**elem, Type::Struct(_)) => {
+                    in_stmts.push(quote! { let #ident = &#ident; });
+                    *input = *std::mem::take(elem);
+                },
+                // rejects invalid discriminants
+                Type::Enum(enum_ident, repr) => {
+                    in_stmts.push(quote! { let #ident = #enum_ident::__from_repr(#ident); });
+                    *input = Type::Numeric(*repr);
+                },
+                // borrowed from a typed array. an empty array may be passed as
+                // a null pointer
+                _ if input.buffer_slice_elem().is_some() => {
+                    let len = format_ident!("arg_{i}_len");
+                    let elem = input.buffer_slice_elem().unwrap().clone();
+                    if let Type::RefMut(_) = input {
+                        in_stmts.push(quote! {
+                            let #ident: &mut [#elem] = if #ident.is_null() {
+                                &mut []
+                            } else {
+                                unsafe { std::slice::from_raw_parts_mut(#ident, #len) }
+                            };
+                        });
+                        *input = Type::PtrMut(Box::new(elem));
+                    } else {
+                        in_stmts.push(quote! {
+                            let #ident: &[#elem] = if #ident.is_null() {
+                                &[]
+                            } else {
+                                unsafe { std::slice::from_raw_parts(#ident, #len) }
+                            };
+                        });
+                        *input = Type::Ptr(Box::new(elem));
+                    }
+                    len_args.push((i, len));
+                },
+                // encoded by the caller into a utf-8 buffer
+                Type::Ref(elem) if js_string && **elem == Type::Str => {
+                    let len = format_ident!("arg_{i}_len");
+                    let str = js_str(&ident, &len);
+                    in_stmts.push(quote! { let #ident: &str = #str; });
+                    *input = Type::Ptr(Box::new(Type::Numeric(TypeNumeric::U8)));
+                    len_args.push((i, len));
+                },
+                Type::String if js_string => {
+                    let len = format_ident!("arg_{i}_len");
+                    let str = js_str(&ident, &len);
+                    in_stmts.push(quote! { let #ident = std::string::String::from(#str); });
+                    *input = Type::Ptr(Box::new(Type::Numeric(TypeNumeric::U8)));
+                    len_args.push((i, len));
+                },
                 Type::Ref(elem) => {
                     in_stmts.push(quote! { let #ident = unsafe { &*#ident }; });
                     *input = Type::Ptr(std::mem::take(elem));
@@ -382,6 +698,63 @@ impl ItemFn {
                     );
                     *input = Type::PtrMut(std::mem::take(elem));
                 },
+                Type::Option(elem) => match &mut **elem {
+                    Type::Ref(elem) => {
+                        in_stmts.push(quote! { let #ident = unsafe { #ident.as_ref() }; });
+                        *input = Type::Ptr(std::mem::take(elem));
+                    },
+                    Type::RefMut(elem) => {
+                        in_stmts.push(quote! { let #ident = unsafe { #ident.as_mut() }; });
+                        *input = Type::PtrMut(std::mem::take(elem));
+                    },
+                    Type::Box(elem) => {
+                        in_stmts.push(quote! {
+                            let #ident = if #ident.is_null() {
+                                std::option::Option::None
+                            } else {
+                                std::option::Option::Some(unsafe { std::boxed::Box::from_raw(#ident) })
+                            };
+                        });
+                        *input = Type::PtrMut(std::mem::take(elem));
+                    },
+                    // the value is read from a buffer owned by the caller
+                    Type::Char => {
+                        in_stmts.push(quote! {
+                            let #ident = unsafe { #ident.as_ref() }.map(|value| {
+                                std::primitive::char::from_u32(*value).expect("invalid unicode scalar value")
+                            });
+                        });
+                        *input = Type::Ptr(Box::new(Type::Numeric(TypeNumeric::U32)));
+                    },
+                    Type::Bool => {
+                        in_stmts.push(quote! {
+                            let #ident = unsafe { #ident.as_ref() }.map(|value| *value != 0);
+                        });
+                        *input = Type::Ptr(Box::new(Type::Numeric(TypeNumeric::U8)));
+                    },
+                    Type::Numeric(_) => {
+                        in_stmts.push(quote! { let #ident = unsafe { #ident.as_ref() }.copied(); });
+                        *input = Type::Ptr(std::mem::take(elem));
+                    },
+                    // raw pointers are passed as they are, with null as `None`
+                    Type::Ptr(_) | Type::PtrMut(_) => {
+                        in_stmts
+                            .push(quote! { let #ident = (!#ident.is_null()).then_some(#ident); });
+                        *input = std::mem::take(&mut **elem);
+                    },
+                    // already ffi-safe, as function pointers are non-null
+                    Type::FnPtr(_) => (),
+                    elem => {
+                        in_stmts.push(quote! {
+                            let #ident = if #ident.is_null() {
+                                std::option::Option::None
+                            } else {
+                                std::option::Option::Some(unsafe { *std::boxed::Box::from_raw(#ident) })
+                            };
+                        });
+                        *input = Type::PtrMut(Box::new(std::mem::take(elem)));
+                    },
+                },
                 rest => {
                     in_stmts.push(
                         quote! { let #ident = unsafe { *std::boxed::Box::from_raw(#ident) }; },
@@ -392,14 +765,41 @@ impl ItemFn {
             args.push(ident);
         }
 
-        *out_stmt = match output {
+        *out_stmt = if js_string && *output == Type::String {
+            // returned as a boxed utf-8 buffer and its length, which is decoded
+            // and freed by the caller
+            let len = format_ident!("out_len");
+            let usize = Type::Numeric(TypeNumeric::Usize);
+            out_args.push((len.clone(), Type::PtrMut(Box::new(usize))));
+            *output = Type::PtrMut(Box::new(Type::Numeric(TypeNumeric::U8)));
+            Some(quote! {
+                unsafe { *#len = out.len() };
+                std::boxed::Box::into_raw(out.into_boxed_str()).cast::<std::primitive::u8>()
+            })
+        } else {
+            Self::transform_output(output, out_args)
+        };
+    }
+
+    /// Lowers a result type into its ffi-safe representation, returning the
+    /// expression that converts the result `out` into the lowered value
+    fn transform_output(
+        output: &mut Type,
+        out_args: &mut Vec<(Ident, Type)>,
+    ) -> Option<TokenStream> {
+        match output {
             Type::Void
             | Type::Numeric(_)
             | Type::Bool
             | Type::Char
             | Type::Ptr(_)
             | Type::PtrMut(_)
-            | Type::FnPtr(_) => None,
+            | Type::FnPtr(_)
+            | Type::Struct(_) => None,
+            Type::Enum(_, repr) => {
+                *output = Type::Numeric(*repr);
+                Some(quote! { out as #output })
+            },
             Type::Ref(elem) => {
                 *output = Type::Ptr(std::mem::take(elem));
                 Some(quote! { &raw const *out })
@@ -412,11 +812,90 @@ impl ItemFn {
                 *output = Type::Ptr(std::mem::take(elem));
                 Some(quote! { std::boxed::Box::into_raw(out) })
             },
+            Type::Option(elem) => match &mut **elem {
+                Type::Ref(elem) => {
+                    *output = Type::Ptr(std::mem::take(elem));
+                    Some(quote! { out.map_or(std::ptr::null(), |out| &raw const *out) })
+                },
+                Type::RefMut(elem) => {
+                    *output = Type::PtrMut(std::mem::take(elem));
+                    Some(quote! { out.map_or(std::ptr::null_mut(), |out| &raw mut *out) })
+                },
+                Type::Box(elem) => {
+                    *output = Type::PtrMut(std::mem::take(elem));
+                    Some(quote! { out.map_or(std::ptr::null_mut(), std::boxed::Box::into_raw) })
+                },
+                Type::Ptr(_) => {
+                    *output = std::mem::take(&mut **elem);
+                    Some(quote! { out.unwrap_or(std::ptr::null()) })
+                },
+                Type::PtrMut(_) => {
+                    *output = std::mem::take(&mut **elem);
+                    Some(quote! { out.unwrap_or(std::ptr::null_mut()) })
+                },
+                Type::FnPtr(_) => None,
+                // return a tag and write the value to a buffer owned by the caller
+                elem if elem.is_buffer_elem() => {
+                    let ident = format_ident!("out_value");
+                    let (value_ty, value) = match elem {
+                        Type::Char => (Type::Numeric(TypeNumeric::U32), quote! { out as u32 }),
+                        Type::Bool => (Type::Numeric(TypeNumeric::U8), quote! { out as u8 }),
+                        elem => (std::mem::take(elem), quote! { out }),
+                    };
+                    out_args.push((ident.clone(), Type::PtrMut(Box::new(value_ty))));
+                    *output = Type::Bool;
+                    Some(quote! {
+                        match out {
+                            std::option::Option::Some(out) => {
+                                unsafe { *#ident = #value };
+                                true
+                            },
+                            std::option::Option::None => false,
+                        }
+                    })
+                },
+                elem => {
+                    *output = Type::PtrMut(Box::new(std::mem::take(elem)));
+                    Some(quote! {
+                        out.map_or(std::ptr::null_mut(), |out| std::boxed::Box::into_raw(std::boxed::Box::from(out)))
+                    })
+                },
+            },
+            // return the error message as a nullable string pointer and write
+            // the value to a buffer owned by the caller
+            Type::Result(ok, _) => {
+                let ident = format_ident!("out_ok");
+                let ok_stmt = Self::transform_output(ok, out_args);
+                let ok_arm = match &**ok {
+                    Type::Void => quote! { std::result::Result::Ok(()) => std::ptr::null_mut(), },
+                    ok => {
+                        out_args.push((ident.clone(), Type::PtrMut(Box::new(ok.clone()))));
+                        let ok_stmt = ok_stmt.map(|ok_stmt| quote! { let out = #ok_stmt; });
+                        quote! {
+                            std::result::Result::Ok(out) => {
+                                #ok_stmt
+                                unsafe { *#ident = out };
+                                std::ptr::null_mut()
+                            },
+                        }
+                    },
+                };
+                *output = Type::PtrMut(Box::new(Type::Numeric(TypeNumeric::U8)));
+                Some(quote! {
+                    match out {
+                        #ok_arm
+                        std::result::Result::Err(err) => {
+                            let message = err.to_string().replace('\0', "");
+                            std::ffi::CString::new(message).unwrap().into_raw().cast()
+                        },
+                    }
+                })
+            },
             rest => {
                 *rest = Type::Ptr(Box::new(std::mem::take(rest)));
                 Some(quote! { std::boxed::Box::into_raw(std::boxed::Box::from(out)) })
             },
-        };
+        }
     }
 }
 
@@ -424,7 +903,6 @@ impl ToTokens for ItemFn {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let ItemFn {
             vis,
-            const_,
             unsafe_,
             ident,
             inputs,
@@ -436,7 +914,15 @@ impl ToTokens for ItemFn {
             args,
             in_stmts,
             out_stmt,
+            out_args,
+            len_args,
+            ctx_args,
+            task_output,
+            send_args,
             self_ty,
+            trait_,
+            interned,
+            ..
         } = block;
 
         // omit unit `()` type from shim wrapper's parameters
@@ -450,13 +936,90 @@ impl ToTokens for ItemFn {
                 call_args.push(quote! { #arg });
                 fn_args.push(quote! { #arg: #input });
             }
+            if let Some((_, len)) = len_args.iter().find(|(index, _)| *index == i) {
+                fn_args.push(quote! { #len: std::primitive::usize });
+            }
+            for (_, ctx, ty) in ctx_args.iter().filter(|(index, ..)| *index == i) {
+                fn_args.push(quote! { #ctx: #ty });
+            }
         }
-
-        let mut call_expr = if let Some(self_ty) = self_ty {
+        let mut out_args: Vec<TokenStream> = out_args
+            .iter()
+            .map(|(arg, ty)| quote! { #arg: #ty })
+            .collect();
+
+        let mut call_expr = if let (Some(self_ty), Some(trait_)) = (self_ty, trait_) {
+            quote! { <#self_ty as #trait_> :: #ident }
+        } else if let Some(self_ty) = self_ty {
             quote! { #self_ty :: #ident }
         } else {
             quote! { #ident }
         };
+        let sentinel = sentinel(output);
+        // a non-blocking call runs on a thread of deno's pool, so the message
+        // of its panic is written into a slot owned by the caller instead. so
+        // is the one of a call without a result, which has no sentinel
+        let slot = format_ident!("out_panic");
+        let panic_slot =
+            self.attr.has_non_blocking() || (*output == Type::Void && !in_utils_crate());
+        if panic_slot {
+            out_args.push(quote! { #slot: *mut *mut std::primitive::u8 });
+        }
+        let ident = self.shim_ident();
+        // the shim calls the function even if it is deprecated
+        let allow = if self.attr.deprecated.is_some() {
+            quote! { #[allow(deprecated)] }
+        } else {
+            TokenStream::new()
+        };
+
+        // an `async` function spawns its future and returns the task, whose
+        // result is taken by a second shim once `done` is called
+        if let Some(task_output) = task_output {
+            let task = match task_output {
+                Type::Void => quote! { deno_bindgen2::RustTask<()> },
+                task_output => quote! { deno_bindgen2::RustTask<#task_output> },
+            };
+            let spawn = catch_unwind(
+                quote! {
+                    #(#in_stmts)*
+                    deno_bindgen2::RustTask::spawn(#call_expr ( #(#call_args),* ), done)
+                },
+                quote! { std::ptr::null_mut() },
+                None,
+            );
+            let take = quote! { unsafe { std::boxed::Box::from_raw(task) }.take() };
+            let (take, output) = match output {
+                Type::Void => (quote! { #take; }, TokenStream::new()),
+                output => match out_stmt {
+                    Some(out_stmt) => {
+                        (quote! { let out = #take; #out_stmt }, quote! { -> #output })
+                    },
+                    None => (take, quote! { -> #output }),
+                },
+            };
+            let take = catch_unwind(take, sentinel, panic_slot.then_some(&slot));
+            let await_ident = self.await_ident();
+
+            let export_shim = export_name(&ident, *interned);
+            let export_await = export_name(&await_ident, *interned);
+
+            tokens.extend(quote! {
+                #export_shim
+                #allow
+                #vis #unsafe_ extern "C" fn #ident ( #(#fn_args,)* done: extern "C" fn() ) -> *mut #task {
+                    #spawn
+                }
+
+                #export_await
+                #vis extern "C" fn #await_ident ( task: *mut #task, #(#out_args),* ) #output {
+                    #take
+                }
+            });
+            return;
+        }
+        fn_args.extend(out_args);
+
         let output = match output {
             Type::Void => {
                 if out_stmt.is_some() {
@@ -475,22 +1038,108 @@ impl ToTokens for ItemFn {
             },
         };
 
-        let ident = match self_ty {
-            Some(self_ty) => format_ident!("__{}__{}", self_ty.to_string(), ident.to_string()),
-            None => format_ident!("__{}", ident.to_string()),
-        };
-
-        tokens.extend(quote! {
-            #[unsafe(no_mangle)]
-            #vis #const_ #unsafe_ extern "C" fn #ident ( #(#fn_args),* ) #output {
+        let body = catch_unwind(
+            quote! {
                 #(#in_stmts)*
                 #call_expr
                 #out_stmt
+            },
+            sentinel,
+            panic_slot.then_some(&slot),
+        );
+
+        // a shared reference `&T` is only `Send` if `T` is `Sync`, so this
+        // also asserts that borrowed arguments can be shared across threads
+        if !send_args.is_empty() {
+            tokens.extend(quote! {
+                const _: fn() = || {
+                    fn assert_send<T: ?std::marker::Sized + std::marker::Send>() {}
+                    #(assert_send::<#send_args>();)*
+                };
+            });
+        }
+
+        let export_shim = export_name(&ident, *interned);
+        tokens.extend(quote! {
+            #export_shim
+            #allow
+            #vis #unsafe_ extern "C" fn #ident ( #(#fn_args),* ) #output {
+                #body
             }
         });
     }
 }
 
+/// The `export_name` attribute of a shim. Shims are exported under the path of
+/// the module they are expanded in, `crate::module::__shim`, so that items of
+/// the same name in different modules do not collide. Crate-wide shims are
+/// exported under the crate name alone
+pub fn export_name(ident: &Ident, crate_level: bool) -> TokenStream {
+    let name = format!("::{ident}");
+    if crate_level {
+        quote! { #[unsafe(export_name = concat!(env!("CARGO_CRATE_NAME"), #name))] }
+    } else {
+        quote! { #[unsafe(export_name = concat!(module_path!(), #name))] }
+    }
+}
+
+/// Catches a panic before it unwinds across the ffi boundary. Its message is
+/// stored for the caller to retrieve, in the given slot if any, and a sentinel
+/// value is returned in place of the result
+fn catch_unwind(body: TokenStream, sentinel: TokenStream, slot: Option<&Ident>) -> TokenStream {
+    let store = match slot {
+        Some(slot) => quote! { unsafe { deno_bindgen2::RustPanic::store_in(payload, #slot) }; },
+        None => quote! { deno_bindgen2::RustPanic::store(payload); },
+    };
+    quote! {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| { #body })) {
+            std::result::Result::Ok(out) => out,
+            std::result::Result::Err(payload) => {
+                #store
+                #sentinel
+            },
+        }
+    }
+}
+
+/// Rebuilds a `&str` from a utf-8 buffer and its length. An empty string may be
+/// passed as a null pointer
+fn js_str(ptr: &Ident, len: &Ident) -> TokenStream {
+    quote! {
+        if #ptr.is_null() {
+            ""
+        } else {
+            std::str::from_utf8(unsafe { std::slice::from_raw_parts(#ptr, #len) })
+                .expect("invalid utf-8 string")
+        }
+    }
+}
+
+/// Whether the shims are expanded in the utils crate, whose bindings are
+/// written by hand against shims without a panic slot
+fn in_utils_crate() -> bool {
+    #[cfg(feature = "macro")]
+    {
+        env_var("CARGO_CRATE_NAME").is_ok_and(|name| name == "deno_bindgen2_utils")
+    }
+    #[cfg(not(feature = "macro"))]
+    {
+        false
+    }
+}
+
+/// The value returned by a shim in place of its result when a panic is caught
+fn sentinel(output: &Type) -> TokenStream {
+    match output {
+        Type::Void => TokenStream::new(),
+        // function pointers have no null value
+        Type::FnPtr(_) => quote! { std::process::abort() },
+        // the remaining ffi-safe types, including fat raw pointers, are valid
+        // when zeroed
+        _ => quote! { unsafe { std::mem::zeroed() } },
+    }
+}
+
 /* -------------------------------------------------------------------------- */
 
 // MARK: print tests
@@ -544,14 +1193,272 @@ mod print_tests {
                 fn test_fn() {}
             },
             {
-                #[unsafe(no_mangle)]
-                extern "C" fn __test_fn() {
-                    test_fn();
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(out_panic: *mut *mut std::primitive::u8) {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        test_fn();
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            unsafe { deno_bindgen2::RustPanic::store_in(payload, out_panic) };
+                        },
+                    }
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_option() {
+        pretty_test!(
+            {
+                fn test_fn(arg0: Option<&u8>) -> Option<u32> {}
+            },
+            {
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(
+                    arg_0: *const std::primitive::u8,
+                    out_value: *mut std::primitive::u32,
+                ) -> std::primitive::bool {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let arg_0 = unsafe { arg_0.as_ref() };
+                        let out = test_fn(arg_0);
+                        match out {
+                            std::option::Option::Some(out) => {
+                                unsafe { *out_value = out };
+                                true
+                            },
+                            std::option::Option::None => false,
+                        }
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            deno_bindgen2::RustPanic::store(payload);
+                            unsafe { std::mem::zeroed() }
+                        },
+                    }
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_option_ptr() {
+        pretty_test!(
+            {
+                fn test_fn(
+                    arg0: Option<*const u8>,
+                    arg1: Option<extern "C" fn()>,
+                ) -> Option<*mut u8> {
+                }
+            },
+            {
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(
+                    arg_0: *const std::primitive::u8,
+                    arg_1: std::option::Option<extern "C" fn()>,
+                ) -> *mut std::primitive::u8 {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let arg_0 = (!arg_0.is_null()).then_some(arg_0);
+                        let out = test_fn(arg_0, arg_1);
+                        out.unwrap_or(std::ptr::null_mut())
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            deno_bindgen2::RustPanic::store(payload);
+                            unsafe { std::mem::zeroed() }
+                        },
+                    }
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_result() {
+        pretty_test!(
+            {
+                fn test_fn() -> Result<Box<u8>, String> {}
+            },
+            {
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(
+                    out_ok: *mut *const std::primitive::u8,
+                ) -> *mut std::primitive::u8 {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let out = test_fn();
+                        match out {
+                            std::result::Result::Ok(out) => {
+                                let out = std::boxed::Box::into_raw(out);
+                                unsafe { *out_ok = out };
+                                std::ptr::null_mut()
+                            },
+                            std::result::Result::Err(err) => {
+                                let message = err.to_string().replace('\0', "");
+                                std::ffi::CString::new(message).unwrap().into_raw().cast()
+                            },
+                        }
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            deno_bindgen2::RustPanic::store(payload);
+                            unsafe { std::mem::zeroed() }
+                        },
+                    }
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_buffer() {
+        pretty_test!(
+            {
+                fn test_fn(arg0: &[u8], arg1: &mut [f64]) {}
+            },
+            {
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(
+                    arg_0: *const std::primitive::u8,
+                    arg_0_len: std::primitive::usize,
+                    arg_1: *mut std::primitive::f64,
+                    arg_1_len: std::primitive::usize,
+                    out_panic: *mut *mut std::primitive::u8,
+                ) {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let arg_0: &[std::primitive::u8] = if arg_0.is_null() {
+                            &[]
+                        } else {
+                            unsafe { std::slice::from_raw_parts(arg_0, arg_0_len) }
+                        };
+                        let arg_1: &mut [std::primitive::f64] = if arg_1.is_null() {
+                            &mut []
+                        } else {
+                            unsafe { std::slice::from_raw_parts_mut(arg_1, arg_1_len) }
+                        };
+                        test_fn(arg_0, arg_1);
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            unsafe { deno_bindgen2::RustPanic::store_in(payload, out_panic) };
+                        },
+                    }
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_js_string() {
+        pretty_test!(
+            {
+                #[doc = "deno_bindgen_js_string"]
+                fn test_fn(arg0: &str, arg1: String) -> String {}
+            },
+            {
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(
+                    arg_0: *const std::primitive::u8,
+                    arg_0_len: std::primitive::usize,
+                    arg_1: *const std::primitive::u8,
+                    arg_1_len: std::primitive::usize,
+                    out_len: *mut std::primitive::usize,
+                ) -> *mut std::primitive::u8 {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let arg_0: &str = if arg_0.is_null() {
+                            ""
+                        } else {
+                            std::str::from_utf8(unsafe {
+                                std::slice::from_raw_parts(arg_0, arg_0_len)
+                            })
+                            .expect("invalid utf-8 string")
+                        };
+                        let arg_1 = std::string::String::from(if arg_1.is_null() {
+                            ""
+                        } else {
+                            std::str::from_utf8(unsafe {
+                                std::slice::from_raw_parts(arg_1, arg_1_len)
+                            })
+                            .expect("invalid utf-8 string")
+                        });
+                        let out = test_fn(arg_0, arg_1);
+                        unsafe { *out_len = out.len() };
+                        std::boxed::Box::into_raw(out.into_boxed_str()).cast::<std::primitive::u8>()
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            deno_bindgen2::RustPanic::store(payload);
+                            unsafe { std::mem::zeroed() }
+                        },
+                    }
                 }
             }
         );
     }
 
+    #[test]
+    fn test_struct() {
+        let structs = StructTable::parse_env("Point\n");
+        let enums = EnumTable::default();
+        let mut item_fn = parse_quote!(ItemFn, fn test_fn(arg0: Point, arg1: &Point) -> Point {});
+        item_fn.resolve_types(&structs, &enums).unwrap();
+        item_fn.transform();
+        dbg_assert!(
+            crate::prettify!(item_fn.to_token_stream().to_string().as_str()),
+            crate::prettify!(stringify! {
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(arg_0: Point, arg_1: Point) -> Point {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let arg_1 = &arg_1;
+                        test_fn(arg_0, arg_1)
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            deno_bindgen2::RustPanic::store(payload);
+                            unsafe { std::mem::zeroed() }
+                        },
+                    }
+                }
+            })
+        );
+
+        let mut item_fn = parse_quote!(ItemFn, fn test_fn(arg0: Option<Point>) {});
+        assert!(item_fn.resolve_types(&structs, &enums).is_err());
+        let mut item_fn = parse_quote!(ItemFn, fn test_fn(arg0: *const Point) {});
+        assert!(item_fn.resolve_types(&structs, &enums).is_ok());
+    }
+
+    #[test]
+    fn test_enum() {
+        let structs = StructTable::default();
+        let enums = EnumTable::parse_env("Mode u8\n");
+        let mut item_fn = parse_quote!(ItemFn, fn test_fn(arg0: Mode) -> Mode {});
+        item_fn.resolve_types(&structs, &enums).unwrap();
+        item_fn.transform();
+        dbg_assert!(
+            crate::prettify!(item_fn.to_token_stream().to_string().as_str()),
+            crate::prettify!(stringify! {
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(arg_0: std::primitive::u8) -> std::primitive::u8 {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let arg_0 = Mode::__from_repr(arg_0);
+                        let out = test_fn(arg_0);
+                        out as std::primitive::u8
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            deno_bindgen2::RustPanic::store(payload);
+                            unsafe { std::mem::zeroed() }
+                        },
+                    }
+                }
+            })
+        );
+
+        let mut item_fn = parse_quote!(ItemFn, fn test_fn(arg0: Vec<Mode>) {});
+        assert!(item_fn.resolve_types(&structs, &enums).is_err());
+    }
+
     #[test]
     #[should_panic]
     fn test_non_fn() {
@@ -562,4 +1469,82 @@ mod print_tests {
             {}
         );
     }
+
+    #[test]
+    fn test_async() {
+        pretty_test!(
+            {
+                async fn test_fn(arg0: u32) -> String {}
+            },
+            {
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(
+                    arg_0: std::primitive::u32,
+                    done: extern "C" fn(),
+                ) -> *mut deno_bindgen2::RustTask<std::string::String> {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        deno_bindgen2::RustTask::spawn(test_fn(arg_0), done)
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            deno_bindgen2::RustPanic::store(payload);
+                            std::ptr::null_mut()
+                        },
+                    }
+                }
+
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn__await"))]
+                extern "C" fn __test_fn__await(
+                    task: *mut deno_bindgen2::RustTask<std::string::String>,
+                ) -> *const std::string::String {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let out = unsafe { std::boxed::Box::from_raw(task) }.take();
+                        std::boxed::Box::into_raw(std::boxed::Box::from(out))
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            deno_bindgen2::RustPanic::store(payload);
+                            unsafe { std::mem::zeroed() }
+                        },
+                    }
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_non_blocking() {
+        pretty_test!(
+            {
+                #[doc = "deno_bindgen_non_blocking"]
+                fn test_fn(arg0: &Counter, arg1: String, arg2: *const u8) -> u32 {}
+            },
+            {
+                const _: fn() = || {
+                    fn assert_send<T: ?std::marker::Sized + std::marker::Send>() {}
+                    assert_send::<&Counter>();
+                    assert_send::<std::string::String>();
+                };
+                #[unsafe(export_name = concat!(module_path!(), "::__test_fn"))]
+                extern "C" fn __test_fn(
+                    arg_0: *const Counter,
+                    arg_1: *mut std::string::String,
+                    arg_2: *const std::primitive::u8,
+                    out_panic: *mut *mut std::primitive::u8,
+                ) -> std::primitive::u32 {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let arg_0 = unsafe { &*arg_0 };
+                        let arg_1 = unsafe { *std::boxed::Box::from_raw(arg_1) };
+                        test_fn(arg_0, arg_1, arg_2)
+                    })) {
+                        std::result::Result::Ok(out) => out,
+                        std::result::Result::Err(payload) => {
+                            unsafe { deno_bindgen2::RustPanic::store_in(payload, out_panic) };
+                            unsafe { std::mem::zeroed() }
+                        },
+                    }
+                }
+            }
+        );
+    }
 }
@@ -1,5 +1,9 @@
+use syn::parse::Parser;
+
 use crate::rust::util::*;
-use crate::rust::{Attribute, ItemFn};
+use crate::rust::{
+    assoc_fn, Association, Attribute, EnumTable, ItemFn, Marker, StructTable, Type, TypeNumeric,
+};
 
 /* -------------------------------------------------------------------------- */
 
@@ -9,10 +13,141 @@ use crate::rust::{Attribute, ItemFn};
 pub struct ItemImpl {
     pub attr:    Attribute,
     pub unsafe_: Option<Token![unsafe]>,
+    /// the trait of an `impl Trait for T` block
+    pub trait_:  Option<syn::Path>,
     pub self_ty: Ident,
     pub items:   Vec<ItemFn>,
 }
 
+/// A well-known trait, mapped onto a javascript protocol of the class instead
+/// of exporting the methods of its impl block
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Protocol {
+    Display,    // toString()
+    Debug,      // [Symbol.for("Deno.customInspect")]()
+    Clone,      // clone()
+    PartialEq,  // equals(other)
+    PartialOrd, // compareTo(other)
+    Ord,        // compareTo(other)
+    Default,    // static default()
+    Iterator,   // [Symbol.iterator]()
+}
+
+impl Protocol {
+    /// Matches the last segment of a trait path, i.e. `std::fmt::Display`
+    pub fn from_path(path: &syn::Path) -> Option<Self> {
+        let segment = path.segments.last()?;
+        match segment.ident.to_string().as_str() {
+            "Display" => Some(Self::Display),
+            "Debug" => Some(Self::Debug),
+            "Clone" => Some(Self::Clone),
+            "PartialEq" => Some(Self::PartialEq),
+            "PartialOrd" => Some(Self::PartialOrd),
+            "Ord" => Some(Self::Ord),
+            "Default" => Some(Self::Default),
+            "Iterator" => Some(Self::Iterator),
+            _ => None,
+        }
+    }
+
+    /// The function exported for this trait, called through the std trait or
+    /// one of the adapters of `deno_bindgen2::protocol`. The body of the impl
+    /// block is only read for the `Item` type of an iterator
+    ///
+    /// - `to_string(&self) -> String`
+    /// - `inspect(&self) -> String`
+    /// - `clone(&self) -> Self`
+    /// - `eq(&self, &Self) -> bool`
+    /// - `partial_compare(&self, &Self) -> f64`
+    /// - `compare(&self, &Self) -> i8`
+    /// - `default() -> Self`
+    /// - `next(&mut self) -> Option<Self::Item>`
+    pub fn item(self, self_ty: &Ident, body: ParseStream) -> Result<ItemFn> {
+        let mut item_ty = None;
+        while !body.is_empty() {
+            match body.parse::<syn::ImplItem>()? {
+                syn::ImplItem::Type(ty) if ty.ident == "Item" => item_ty = Some(ty.ty),
+                _ => (),
+            }
+        }
+
+        let this = Type::UserDefined(self_ty.clone());
+        let this_ref = || Type::Ref(Box::new(this.clone()));
+        let (ident, trait_, inputs, output): (_, syn::Path, _, _) = match self {
+            Self::Display => (
+                "to_string",
+                syn::parse_quote!(std::string::ToString),
+                vec![this_ref()],
+                Type::String,
+            ),
+            Self::Debug => (
+                "inspect",
+                syn::parse_quote!(deno_bindgen2::protocol::Inspect),
+                vec![this_ref()],
+                Type::String,
+            ),
+            Self::Clone => (
+                "clone",
+                syn::parse_quote!(std::clone::Clone),
+                vec![this_ref()],
+                this.clone(),
+            ),
+            Self::PartialEq => (
+                "eq",
+                syn::parse_quote!(std::cmp::PartialEq),
+                vec![this_ref(), this_ref()],
+                Type::Bool,
+            ),
+            Self::PartialOrd => (
+                "partial_compare",
+                syn::parse_quote!(deno_bindgen2::protocol::PartialCompare),
+                vec![this_ref(), this_ref()],
+                Type::Numeric(TypeNumeric::F64),
+            ),
+            Self::Ord => (
+                "compare",
+                syn::parse_quote!(deno_bindgen2::protocol::Compare),
+                vec![this_ref(), this_ref()],
+                Type::Numeric(TypeNumeric::I8),
+            ),
+            Self::Default => (
+                "default",
+                syn::parse_quote!(std::default::Default),
+                Vec::new(),
+                this.clone(),
+            ),
+            Self::Iterator => {
+                let Some(item_ty) = item_ty else {
+                    return Err(Error::new(
+                        self_ty.span(),
+                        "missing `type Item` in the iterator implementation",
+                    ));
+                };
+                let parse_type = |input: ParseStream| Type::parse(input, Some(self_ty));
+                (
+                    "next",
+                    syn::parse_quote!(std::iter::Iterator),
+                    vec![Type::RefMut(Box::new(this.clone()))],
+                    Type::Option(Box::new(parse_type.parse2(item_ty.into_token_stream())?)),
+                )
+            },
+        };
+
+        let mut item = assoc_fn(self_ty, ident, inputs, output);
+        item.assoc = match self {
+            Self::Default => Some(Association::Static),
+            Self::Iterator => Some(Association::InstanceMut),
+            _ => Some(Association::Instance),
+        };
+        // strings are returned to javascript as a primitive `string`
+        if matches!(self, Self::Display | Self::Debug) {
+            item.attr.markers.push(Marker::JsString);
+        }
+        item.block.trait_ = Some(trait_);
+        Ok(item)
+    }
+}
+
 // MARK: parse
 
 impl Parse for ItemImpl {
@@ -44,6 +179,20 @@ impl ItemImpl {
             ));
         }
 
+        // `impl Trait for T`. traits with type arguments are rejected below as
+        // a self type with type arguments
+        let fork = input.fork();
+        let trait_ = match (
+            fork.call(syn::Path::parse_mod_style),
+            fork.parse::<Token![for]>(),
+        ) {
+            (Ok(path), Ok(_)) => {
+                input.advance_to(&fork);
+                Some(path)
+            },
+            _ => None,
+        };
+
         let fork = input.fork();
 
         // is the leading colon okay if the path is global? this may refer to an item
@@ -90,13 +239,6 @@ impl ItemImpl {
             }
         };
 
-        if let Some(for_) = input.parse::<Option<Token![for]>>()? {
-            return Err(Error::new(
-                for_.span(),
-                "unexpected token: trait implements are not supported",
-            ));
-        }
-
         if let Some(where_) = input.parse::<Option<Token![where]>>()? {
             return Err(Error::new(
                 where_.span(),
@@ -108,6 +250,17 @@ impl ItemImpl {
         braced!(content in input);
         attr.parse_inner(&content)?;
 
+        // the methods of a well-known trait are replaced by its protocol
+        if let Some(protocol) = trait_.as_ref().and_then(Protocol::from_path) {
+            return Ok(Self {
+                attr,
+                unsafe_,
+                items: vec![protocol.item(&self_ty, &content)?],
+                trait_,
+                self_ty,
+            });
+        }
+
         let mut items = Vec::new();
 
         while !content.is_empty() {
@@ -135,7 +288,20 @@ impl ItemImpl {
                     _ => (),
                 }
 
+                if item.attr.has_constructor()
+                    && items
+                        .iter()
+                        .any(|item: &ItemFn| item.attr.has_constructor())
+                {
+                    return Err(Error::new(
+                        item.ident.span(),
+                        "a class has a single `constructor`",
+                    ));
+                }
+
                 content.advance_to(&fork);
+                let mut item = item;
+                item.block.trait_ = trait_.clone();
                 items.push(item);
             } else {
                 let syn_item = content.call(syn::ImplItem::parse)?;
@@ -151,10 +317,27 @@ impl ItemImpl {
         Ok(Self {
             attr,
             unsafe_,
+            trait_,
             self_ty,
             items,
         })
     }
+
+    /// Replaces the live `#[deno_bindgen(constructor)]` markers on the methods
+    /// of an impl block with their inert form
+    pub fn inert_method_markers(input: TokenStream) -> Result<TokenStream> {
+        let mut item_impl: syn::ItemImpl = syn::parse2(input)?;
+        for item in item_impl.items.iter_mut() {
+            if let syn::ImplItem::Fn(item_fn) = item {
+                Attribute::inert_markers(&mut item_fn.attrs)?;
+            }
+        }
+        Ok(item_impl.into_token_stream())
+    }
+
+    pub fn protocol(&self) -> Option<Protocol> {
+        self.trait_.as_ref().and_then(Protocol::from_path)
+    }
 }
 
 /* -------------------------------------------------------------------------- */
@@ -191,9 +374,81 @@ mod parse_tests {
     }
 
     #[test]
-    #[should_panic]
     fn with_for() {
-        dbg_quote!(ItemImpl, impl Debug for CustomType {});
+        let item_impl = parse_quote!(
+            ItemImpl,
+            impl Greeter for CustomType {
+                fn greet(&self) -> String {}
+            }
+        );
+        dbg_assert!(item_impl.protocol(), None);
+        dbg_assert!(
+            item_impl.items[0].shim_ident().to_string(),
+            "__CustomType__greet"
+        );
+        dbg_assert!(
+            item_impl.items[0].block.trait_,
+            Some(syn::parse_quote!(Greeter))
+        );
+    }
+
+    #[test]
+    fn with_protocol() {
+        let item_impl = parse_quote!(
+            ItemImpl,
+            impl std::fmt::Display for CustomType {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "custom")
+                }
+            }
+        );
+        dbg_assert!(item_impl.protocol(), Some(Protocol::Display));
+        dbg_assert!(item_impl.items.len(), 1);
+        dbg_assert!(
+            item_impl.items[0].shim_ident().to_string(),
+            "__CustomType__to_string"
+        );
+
+        let item_impl = parse_quote!(ItemImpl, impl Default for CustomType {});
+        dbg_assert!(item_impl.items[0].assoc, Some(Association::Static));
+    }
+
+    #[test]
+    fn with_iterator() {
+        let item_impl = parse_quote!(
+            ItemImpl,
+            impl Iterator for Counter {
+                type Item = u32;
+                fn next(&mut self) -> Option<Self::Item> {
+                    None
+                }
+            }
+        );
+        dbg_assert!(item_impl.protocol(), Some(Protocol::Iterator));
+        dbg_assert!(item_impl.items[0].assoc, Some(Association::InstanceMut));
+        dbg_assert!(
+            item_impl.items[0].output,
+            Type::Option(Box::new(Type::Numeric(TypeNumeric::U32)))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_iterator_without_item() {
+        dbg_quote!(
+            ItemImpl,
+            impl Iterator for Counter {
+                fn next(&mut self) -> Option<u32> {
+                    None
+                }
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_for_type_arguments() {
+        dbg_quote!(ItemImpl, impl PartialEq<Other> for CustomType {});
     }
 
     #[test]
@@ -334,6 +589,90 @@ mod parse_tests {
             }
         );
     }
+
+    #[test]
+    fn with_constructor() {
+        let item_impl = parse_quote!(
+            ItemImpl,
+            impl CustomType {
+                #[deno_bindgen(constructor)]
+                fn new(value: u32) -> Self {}
+            }
+        );
+        dbg_assert!(item_impl.items[0].attr.has_constructor(), true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_constructor_receiver() {
+        dbg_quote!(ItemImpl,
+            impl CustomType {
+                #[deno_bindgen(constructor)]
+                fn new(&self) -> Self {}
+            }
+        );
+    }
+
+    #[test]
+    fn with_accessors() {
+        let item_impl = parse_quote!(
+            ItemImpl,
+            impl CustomType {
+                #[deno_bindgen(getter)]
+                fn len(&self) -> usize {}
+                #[deno_bindgen(setter)]
+                fn set_len(&mut self, len: usize) {}
+            }
+        );
+        dbg_assert!(item_impl.items[0].attr.has_getter(), true);
+        dbg_assert!(item_impl.items[1].attr.has_setter(), true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_getter_args() {
+        dbg_quote!(ItemImpl,
+            impl CustomType {
+                #[deno_bindgen(getter)]
+                fn get(&self, index: usize) -> u32 {}
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_setter_receiver() {
+        dbg_quote!(ItemImpl,
+            impl CustomType {
+                #[deno_bindgen(setter)]
+                fn set_len(&self, len: usize) {}
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_field_marker() {
+        dbg_quote!(ItemImpl,
+            impl CustomType {
+                #[deno_bindgen(get)]
+                fn len(&self) -> usize {}
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_many_constructors() {
+        dbg_quote!(ItemImpl,
+            impl CustomType {
+                #[deno_bindgen(constructor)]
+                fn new() -> Self {}
+                #[deno_bindgen(constructor)]
+                fn with_value(value: u32) -> CustomType {}
+            }
+        );
+    }
 }
 
 /* -------------------------------------------------------------------------- */
@@ -341,6 +680,44 @@ mod parse_tests {
 // MARK: print
 
 impl ItemImpl {
+    /// Resolves the structs passed by value and the C-like enums in the
+    /// signatures of this impl block. Methods of a struct passed by value are
+    /// given a copy of the struct, so they cannot take a `&mut self` receiver
+    pub fn resolve_types(&mut self, structs: &StructTable, enums: &EnumTable) -> Result<()> {
+        // exported as typescript enums, which cannot have methods
+        if enums.get(&self.self_ty).is_some() {
+            return Err(Error::new(
+                self.self_ty.span(),
+                format!(
+                    "`{}` is passed as an integer, so it cannot have methods",
+                    self.self_ty
+                ),
+            ));
+        }
+        for item in &mut self.items {
+            if structs.contains(&self.self_ty) && item.assoc == Some(Association::InstanceMut) {
+                return Err(Error::new(
+                    item.ident.span(),
+                    format!(
+                        "`{}` is passed by value, so its methods cannot take `&mut self`",
+                        self.self_ty
+                    ),
+                ));
+            }
+            if structs.contains(&self.self_ty) && item.attr.has_constructor() {
+                return Err(Error::new(
+                    item.ident.span(),
+                    format!(
+                        "`{}` is passed by value, so it is constructed from its fields",
+                        self.self_ty
+                    ),
+                ));
+            }
+            item.resolve_types(structs, enums)?;
+        }
+        Ok(())
+    }
+
     pub fn transform(&mut self) {
         for item in &mut self.items {
             item.transform();
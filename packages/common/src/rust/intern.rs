@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+
+use crate::rust::util::*;
+use crate::rust::{Association, Attribute, Block, Item, ItemFn, ItemTuple, ItemVec, Type};
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: interned api
+
+/// A type whose accessor functions are monomorphized and emitted once for the
+/// whole crate, rather than once for every function that uses it
+#[derive(Clone, Debug, PartialEq)]
+pub enum ItemInterned {
+    Tuple(ItemTuple),
+    Vec(Box<ItemVec>),
+}
+
+impl ItemInterned {
+    pub fn new(ty: &Type) -> Self {
+        match ty {
+            Type::Tuple(_) => Self::Tuple(ItemTuple::new(ty)),
+            Type::Vec(_) => Self::Vec(Box::new(ItemVec::new(ty))),
+            _ => panic!("attempted to intern a type that is not a tuple or `Vec<T>`"),
+        }
+    }
+
+    /// The name of the unit struct and typescript class holding the accessors
+    pub fn ident(&self) -> &Ident {
+        match self {
+            Self::Tuple(item_tuple) => &item_tuple.ident,
+            Self::Vec(item_vec) => &item_vec.ident,
+        }
+    }
+
+    pub fn items(&self) -> Vec<ItemFn> {
+        match self {
+            Self::Tuple(item_tuple) => item_tuple.items(),
+            Self::Vec(item_vec) => item_vec.items(),
+        }
+    }
+}
+
+/// An associated function of an interned type, with a public shim
+pub fn assoc_fn(self_ty: &Ident, ident: &str, inputs: Vec<Type>, output: Type) -> ItemFn {
+    ItemFn {
+        attr: Attribute::default(),
+        vis: Visibility::Public(Default::default()),
+        const_: None,
+        async_: None,
+        unsafe_: None,
+        ident: format_ident!("{ident}"),
+        inputs,
+        output,
+        assoc: Some(Association::Static),
+        block: Block {
+            self_ty: Some(self_ty.clone()),
+            interned: true,
+            ..Default::default()
+        },
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: intern table
+
+/// Maps the name of every interned type to the shim that owns it. The owner is
+/// the first shim in the crate whose signature uses the type, and is the only
+/// one to emit the type's accessor symbols
+///
+/// The table is built by the cli from the expanded source code and passed to
+/// the macro through the [`InternTable::ENV`] environment variable
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InternTable {
+    store: BTreeMap<String, String>,
+}
+
+impl InternTable {
+    pub const ENV: &'static str = "DENO_BINDGEN2_INTERNED";
+
+    /// Builds the intern table of a crate from its `deno_bindgen` items
+    pub fn collect(items: &[Item]) -> Self {
+        let mut table = Self::default();
+        table.collect_items(items);
+        table
+    }
+
+    fn collect_items(&mut self, items: &[Item]) {
+        for item in items {
+            match item {
+                Item::Fn(item_fn) => self.collect_fn(item_fn),
+                Item::Impl(item_impl) => {
+                    for item_fn in &item_impl.items {
+                        self.collect_fn(item_fn);
+                    }
+                },
+                Item::Mod(item_mod) => self.collect_items(&item_mod.items),
+                Item::Struct(_) | Item::Enum(_) | Item::Static(_) => (),
+            }
+        }
+    }
+
+    fn collect_fn(&mut self, item_fn: &ItemFn) {
+        let mut interned = Vec::new();
+        item_fn.collect_interned(&mut interned);
+        for ty in interned {
+            self.store
+                .entry(ItemInterned::new(&ty).ident().to_string())
+                .or_insert_with(|| item_fn.shim_ident().to_string());
+        }
+    }
+
+    /// Reads the table passed by the cli. The table is empty when the crate is
+    /// not being built by the cli
+    #[cfg(feature = "macro")]
+    pub fn from_env() -> Self {
+        match env_var(Self::ENV) {
+            Ok(table) => Self::parse_env(&table),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parses the table from its environment variable representation, one
+    /// `interned_name owner` pair per line
+    pub fn parse_env(table: &str) -> Self {
+        let store = table
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(ident, owner)| (ident.to_string(), owner.to_string()))
+            .collect();
+        Self { store }
+    }
+
+    pub fn to_env(&self) -> String {
+        self.store
+            .iter()
+            .map(|(ident, owner)| format!("{ident} {owner}\n"))
+            .collect()
+    }
+
+    /// The interned types that the given item is responsible for emitting
+    pub fn owned_by(&self, item: &Item) -> Vec<ItemInterned> {
+        let item_fns = match item {
+            Item::Fn(item_fn) => vec![item_fn],
+            Item::Impl(item_impl) => item_impl.items.iter().collect(),
+            _ => return Vec::new(),
+        };
+
+        let mut owned = Vec::new();
+        for item_fn in item_fns {
+            let owner = item_fn.shim_ident().to_string();
+            let mut interned = Vec::new();
+            item_fn.collect_interned(&mut interned);
+            for ty in interned {
+                let item_interned = ItemInterned::new(&ty);
+                if self.store.get(&item_interned.ident().to_string()) == Some(&owner) {
+                    owned.push(item_interned);
+                }
+            }
+        }
+        owned
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: print
+
+impl ToTokens for ItemInterned {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = self.ident();
+        let impl_items = match self {
+            Self::Tuple(item_tuple) => item_tuple.impl_items(),
+            Self::Vec(item_vec) => item_vec.impl_items(),
+        };
+
+        let mut items = self.items();
+        for item in &mut items {
+            item.transform();
+        }
+
+        tokens.extend(quote! {
+            const _: () = {
+                #[allow(non_camel_case_types)]
+                struct #ident;
+
+                impl #ident {
+                    #impl_items
+                }
+
+                #(#items)*
+            };
+        });
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rust::File;
+
+    #[test]
+    fn test_collect() {
+        let file = File::parse_str(
+            quote! {
+                #[doc = "deno_bindgen"]
+                fn first(tup: (u8, String)) -> Box<(usize, u8)> {}
+
+                #[doc = "deno_bindgen"]
+                impl CustomType {
+                    fn second(&self) -> (u8, String) {}
+                    fn third(tup: (usize, u8), borrowed: (u8, &str)) -> Vec<u8> {}
+                }
+            }
+            .to_string()
+            .as_str(),
+        );
+
+        let table = InternTable::collect(&file.items);
+        dbg_assert!(
+            table.to_env(),
+            "RustVec_u8 __CustomType__third\nTuple2_u8_String __first\nTuple2_usize_u8 __first\n"
+        );
+        dbg_assert!(InternTable::parse_env(&table.to_env()), table);
+
+        dbg_assert!(table.owned_by(&file.items[0]).len(), 2);
+        dbg_assert!(table.owned_by(&file.items[1]), vec![ItemInterned::new(
+            &parse_quote!(Type, Vec<u8>)
+        )]);
+    }
+
+    #[test]
+    fn test_pretty_tuple() {
+        let tuple = ItemInterned::new(&parse_quote!(Type, (u8, String)));
+        println!(
+            "{}",
+            crate::prettify!(tuple.to_token_stream().to_string().as_str())
+        );
+    }
+
+    #[test]
+    fn test_pretty_vec() {
+        let vec = ItemInterned::new(&parse_quote!(Type, Vec<String>));
+        println!(
+            "{}",
+            crate::prettify!(vec.to_token_stream().to_string().as_str())
+        );
+    }
+}
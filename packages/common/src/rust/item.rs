@@ -1,5 +1,8 @@
 use crate::rust::util::*;
-use crate::rust::{Attribute, ItemFn, ItemImpl, ItemMod, ItemStruct};
+use crate::rust::{
+    Attribute, EnumTable, ItemEnum, ItemFn, ItemImpl, ItemMod, ItemStatic, ItemStruct, Marker,
+    StructTable,
+};
 
 /* -------------------------------------------------------------------------- */
 
@@ -11,7 +14,8 @@ pub enum Item {
     Impl(ItemImpl),
     Mod(ItemMod),
     Struct(ItemStruct),
-    // Static(ItemStatic), // [!TODO] support static items and data
+    Enum(ItemEnum),
+    Static(ItemStatic),
 }
 
 // MARK: parse
@@ -47,10 +51,7 @@ impl Item {
             && unsafe_.is_ok()
             && fn_.is_ok()
         {
-            let async_ = async_.unwrap();
-            if let Some(async_) = async_ {
-                return Err(Error::new(async_.span, "unsupported async function"));
-            } else if extern_.unwrap().is_some() {
+            if extern_.unwrap().is_some() {
                 // TODO: should extern functions be restricted?
 
                 if let Some(abi) = abi.unwrap() {
@@ -65,12 +66,18 @@ impl Item {
             }
             input.advance_to(&fork);
             let const_ = const_.unwrap();
+            let async_ = async_.unwrap();
             let unsafe_ = unsafe_.unwrap();
             return Ok(Self::Fn(ItemFn::parse_remaining(
-                input, attr, vis, None, const_, unsafe_,
+                input, attr, vis, None, const_, async_, unsafe_,
             )?));
         }
 
+        // a `const fn` was parsed above, so a `const` token here starts an item
+        if input.peek(Token![const]) || input.peek(Token![static]) {
+            return Ok(Self::Static(ItemStatic::parse_remaining(input, attr, vis)?));
+        }
+
         // start a speculative parse to check if there is an `impl` token
         let fork = input.fork();
         let unsafe_ = fork.parse::<Option<Token![unsafe]>>();
@@ -88,7 +95,16 @@ impl Item {
             return Ok(Self::Struct(ItemStruct::parse_remaining(input, attr)?));
         }
 
-        if cfg!(not(feature = "macro")) {
+        let fork = input.fork();
+        let enum_ = fork.parse::<Token![enum]>();
+        if enum_.is_ok() {
+            input.advance_to(&fork);
+            return Ok(Self::Enum(ItemEnum::parse_remaining(input, attr)?));
+        }
+
+        // modules are only parsed by the cli. the macro expands the items of a
+        // module on their own
+        if !in_macro() {
             let fork = input.fork();
             let unsafe_ = fork.parse::<Option<Token![unsafe]>>();
             let mod_ = fork.parse::<Token![mod]>();
@@ -98,7 +114,9 @@ impl Item {
             }
         }
 
-        Err(input.error("failed to parse item: expected `fn`, `impl`"))
+        Err(input.error(
+            "failed to parse item: expected `fn`, `impl`, `struct`, `enum`, `const`, `static`",
+        ))
     }
 
     pub fn parse_many(input: ParseStream, filtered: bool) -> Result<Vec<Self>> {
@@ -114,7 +132,9 @@ impl Item {
                     match &item {
                         Item::Fn(ItemFn { attr, .. })
                         | Item::Impl(ItemImpl { attr, .. })
-                        | Item::Struct(ItemStruct { attr, .. }) => {
+                        | Item::Struct(ItemStruct { attr, .. })
+                        | Item::Enum(ItemEnum { attr, .. })
+                        | Item::Static(ItemStatic { attr, .. }) => {
                             if attr.has_deno_bindgen() {
                                 items.push(item)
                             }
@@ -186,6 +206,15 @@ mod parse_tests {
         dbg_quote!(Item, struct CustomType;);
     }
 
+    #[test]
+    fn test_static() {
+        dbg_quote!(
+            Item,
+            #[doc = "deno_bindgen"]
+            pub static mut COUNT: u32 = 0;
+        );
+    }
+
     #[test]
     #[should_panic]
     #[cfg(feature = "macro")]
@@ -224,6 +253,40 @@ mod parse_tests {
 // MARK: print
 
 impl Item {
+    /// Adds a marker to every function of this item. Used for the markers that
+    /// can be enabled for the whole crate by the cli
+    pub fn push_marker(&mut self, marker: &Marker) {
+        match self {
+            Item::Fn(item_fn) => item_fn.attr.markers.push(marker.clone()),
+            Item::Impl(item_impl) => {
+                for item_fn in &mut item_impl.items {
+                    item_fn.attr.markers.push(marker.clone());
+                }
+            },
+            Item::Mod(item_mod) => {
+                for item in &mut item_mod.items {
+                    item.push_marker(marker);
+                }
+            },
+            Item::Struct(_) | Item::Enum(_) | Item::Static(_) => (),
+        }
+    }
+
+    /// Resolves the structs passed by value and the C-like enums in the
+    /// signatures of this item
+    pub fn resolve_types(&mut self, structs: &StructTable, enums: &EnumTable) -> Result<()> {
+        match self {
+            Item::Fn(item_fn) => item_fn.resolve_types(structs, enums),
+            Item::Impl(item_impl) => item_impl.resolve_types(structs, enums),
+            Item::Mod(item_mod) => item_mod
+                .items
+                .iter_mut()
+                .try_for_each(|item| item.resolve_types(structs, enums)),
+            Item::Static(item_static) => item_static.resolve_types(structs),
+            Item::Struct(_) | Item::Enum(_) => Ok(()),
+        }
+    }
+
     pub fn transform(&mut self) {
         match self {
             Item::Fn(item_fn) => item_fn.transform(),
@@ -239,6 +302,8 @@ impl ToTokens for Item {
             Item::Fn(item_fn) => item_fn.to_token_stream(),
             Item::Impl(item_impl) => item_impl.to_token_stream(),
             Item::Struct(item_struct) => item_struct.to_token_stream(),
+            Item::Enum(item_enum) => item_enum.to_token_stream(),
+            Item::Static(item_static) => item_static.to_token_stream(),
             _ => TokenStream::new(), // do nothing for unsupported types
         });
     }
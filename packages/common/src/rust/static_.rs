@@ -0,0 +1,265 @@
+use crate::rust::util::*;
+use crate::rust::{export_name, Attribute, StructTable, Type};
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: api
+
+/// A `const` or `static` item exported by the crate
+///
+/// Immutable numbers, booleans and strings initialized with a literal are
+/// inlined into the bindings. Other values are read through a static symbol
+/// holding a pointer to the item
+#[derive(Clone, Debug)]
+pub struct ItemStatic {
+    pub attr:   Attribute,
+    pub vis:    Visibility,
+    pub const_: Option<Token![const]>,
+    pub mut_:   Option<Token![mut]>,
+    pub ident:  Ident,
+    pub ty:     Type,
+    pub expr:   Expr,
+}
+
+// MARK: parse
+
+impl Parse for ItemStatic {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut attr = Attribute::default();
+        attr.parse_outer(input)?;
+        let vis = input.parse()?;
+        Self::parse_remaining(input, attr, vis)
+    }
+}
+
+impl ItemStatic {
+    pub fn parse_remaining(input: ParseStream, attr: Attribute, vis: Visibility) -> Result<Self> {
+        let const_ = input.parse::<Option<Token![const]>>()?;
+        let mut_ = if const_.is_none() {
+            input.parse::<Token![static]>()?;
+            input.parse()?
+        } else {
+            None
+        };
+        let ident: Ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+
+        // `'static` is the only lifetime a `const` or `static` item can name
+        let mut ty: syn::Type = input.parse()?;
+        if let syn::Type::Reference(reference) = &mut ty {
+            if reference
+                .lifetime
+                .as_ref()
+                .is_some_and(|lifetime| lifetime.ident == "static")
+            {
+                reference.lifetime = None;
+            }
+        }
+        let parse_type = |input: ParseStream| Type::parse(input, None);
+        let ty = syn::parse::Parser::parse2(parse_type, ty.to_token_stream())?;
+
+        input.parse::<Token![=]>()?;
+        let expr: Expr = input.parse()?;
+        input.parse::<Token![;]>()?;
+
+        let item = Self {
+            attr,
+            vis,
+            const_,
+            mut_,
+            ident,
+            ty,
+            expr,
+        };
+
+        match &item.ty {
+            Type::Numeric(_) | Type::Bool | Type::UserDefined(_) => (),
+            // a string has no ffi-safe representation to be read from
+            Type::Ref(elem) if **elem == Type::Str => {
+                if !item.is_inlined() {
+                    return Err(Error::new(
+                        item.expr.span(),
+                        "exported strings must be immutable and initialized with a string literal",
+                    ));
+                }
+            },
+            _ => {
+                return Err(Error::new(
+                    item.ident.span(),
+                    "unsupported type. expected a number, `bool`, `&'static str` or a `repr(C)` struct",
+                ));
+            },
+        }
+
+        Ok(item)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: parse tests
+
+#[cfg(test)]
+mod parse_tests {
+    use super::*;
+
+    #[test]
+    fn test_const() {
+        let item = parse_quote!(ItemStatic, pub const MAX: u32 = 10;);
+        dbg_assert!(item.is_inlined(), true);
+    }
+
+    #[test]
+    fn test_static_str() {
+        let item = parse_quote!(ItemStatic, static NAME: &'static str = "name";);
+        dbg_assert!(item.ty, Type::Ref(Box::new(Type::Str)));
+        dbg_assert!(item.is_inlined(), true);
+    }
+
+    #[test]
+    fn test_negative() {
+        let item = parse_quote!(ItemStatic, const MIN: i64 = -1;);
+        dbg_assert!(item.is_inlined(), true);
+    }
+
+    #[test]
+    fn test_expr() {
+        let item = parse_quote!(ItemStatic, const MAX: u32 = u16::MAX as u32;);
+        dbg_assert!(item.is_inlined(), false);
+    }
+
+    #[test]
+    fn test_static_mut() {
+        let item = parse_quote!(ItemStatic, static mut COUNT: u32 = 0;);
+        dbg_assert!(item.mut_.is_some(), true);
+        dbg_assert!(item.is_inlined(), false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_static_mut_str() {
+        dbg_quote!(ItemStatic, static mut NAME: &str = "name";);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_unsupported() {
+        dbg_quote!(ItemStatic, static NAMES: Vec<String> = Vec::new(););
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: print
+
+impl ItemStatic {
+    /// The name of the static symbol pointing to this item
+    pub fn shim_ident(&self) -> Ident {
+        format_ident!("__{}", self.ident.to_string())
+    }
+
+    /// Whether the value of this item is inlined into the bindings instead of
+    /// being read through its static symbol
+    pub fn is_inlined(&self) -> bool {
+        let literal = match &self.expr {
+            Expr::Lit(_) => true,
+            Expr::Unary(syn::ExprUnary {
+                op: syn::UnOp::Neg(_),
+                expr,
+                ..
+            }) => matches!(**expr, Expr::Lit(_)),
+            _ => false,
+        };
+        let inlined_ty = match &self.ty {
+            Type::Numeric(_) | Type::Bool => true,
+            Type::Ref(elem) => **elem == Type::Str,
+            _ => false,
+        };
+        self.mut_.is_none() && literal && inlined_ty
+    }
+
+    /// Resolves the type of this item if it names a struct passed by value
+    pub fn resolve_types(&mut self, structs: &StructTable) -> Result<()> {
+        self.ty.resolve_structs(structs)
+    }
+}
+
+impl ToTokens for ItemStatic {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ItemStatic {
+            const_, ident, ty, ..
+        } = self;
+        // the value is written into the bindings, so the item is only marked
+        // as used to keep it from being reported as dead code
+        if self.is_inlined() {
+            tokens.extend(quote! {
+                const _: () = {
+                    let _ = #ident;
+                };
+            });
+            return;
+        }
+        let shim = self.shim_ident();
+
+        // a `const` has no address of its own, so it is borrowed from a
+        // promoted copy instead
+        let ptr = if const_.is_some() {
+            quote! { &#ident }
+        } else {
+            quote! { &raw const #ident }
+        };
+
+        let export_shim = export_name(&shim, false);
+        tokens.extend(quote! {
+            #export_shim
+            static #shim: deno_bindgen2::RustStatic<#ty> = deno_bindgen2::RustStatic::new(#ptr);
+        });
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: print tests
+
+#[cfg(test)]
+mod print_tests {
+    use super::*;
+
+    #[test]
+    fn test_inlined() {
+        let item = parse_quote!(ItemStatic, const MAX: u32 = 10;);
+        let expanded = crate::prettify!(item.to_token_stream().to_string().as_str());
+        let expected = crate::prettify!(stringify!(
+            const _: () = {
+                let _ = MAX;
+            };
+        ));
+        assert_eq!(expanded, expected);
+    }
+
+    #[test]
+    fn test_static_mut() {
+        let item = parse_quote!(ItemStatic, static mut COUNT: u32 = 0;);
+        let expanded = crate::prettify!(item.to_token_stream().to_string().as_str());
+        let expected = crate::prettify!(stringify!(
+            #[unsafe(export_name = concat!(module_path!(), "::__COUNT"))]
+            static __COUNT: deno_bindgen2::RustStatic<std::primitive::u32> =
+                deno_bindgen2::RustStatic::new(&raw const COUNT);
+        ));
+        assert_eq!(expanded, expected);
+    }
+
+    #[test]
+    fn test_const_struct() {
+        let mut item = parse_quote!(ItemStatic, const ORIGIN: Point = Point { x: 0.0, y: 0.0 };);
+        item.resolve_types(&StructTable::parse_env("Point\n"))
+            .unwrap();
+        let expanded = crate::prettify!(item.to_token_stream().to_string().as_str());
+        let expected = crate::prettify!(stringify!(
+            #[unsafe(export_name = concat!(module_path!(), "::__ORIGIN"))]
+            static __ORIGIN: deno_bindgen2::RustStatic<Point> =
+                deno_bindgen2::RustStatic::new(&ORIGIN);
+        ));
+        assert_eq!(expanded, expected);
+    }
+}
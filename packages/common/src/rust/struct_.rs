@@ -1,12 +1,28 @@
+use std::collections::BTreeSet;
+
+use syn::parse::Parser;
+
 use crate::rust::util::*;
-use crate::rust::Attribute;
+use crate::rust::{export_name, Association, Attribute, Block, Item, ItemFn, Marker, Type};
 
 // MARK: api
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ItemStruct {
+    pub attr:   Attribute,
+    pub ident:  Ident,
+    /// fields of the struct. only the types of `#[repr(C)]` structs and of
+    /// fields with accessors are parsed, the others are kept as unsupported
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    /// `get` and `set` markers of the field
     pub attr:  Attribute,
-    pub ident: Ident,
+    /// `None` for the fields of a tuple struct
+    pub ident: Option<Ident>,
+    pub ty:    Type,
 }
 
 
@@ -42,24 +58,282 @@ impl ItemStruct {
         }
 
         let ahead = input.lookahead1();
-        if ahead.peek(Paren) {
-            syn::FieldsUnnamed::parse(input)?;
+        let fields = if ahead.peek(Paren) {
+            let fields = syn::FieldsUnnamed::parse(input)?.unnamed;
             let ahead = input.lookahead1();
             if ahead.peek(Token![;]) {
                 input.parse::<Token![;]>()?;
             } else {
                 return Err(ahead.error());
             }
+            fields
         } else if ahead.peek(Brace) {
-            syn::FieldsNamed::parse(input)?;
+            syn::FieldsNamed::parse(input)?.named
         } else if ahead.peek(Token![;]) {
             input.parse::<Token![;]>()?;
+            Default::default()
         } else {
             return Err(ahead.error());
+        };
+
+        let parse_type = |input: ParseStream| Type::parse(input, None);
+        let fields = fields
+            .into_iter()
+            .map(|field| {
+                let mut field_attr = Attribute::default();
+                let attrs = &field.attrs;
+                (|input: ParseStream| field_attr.parse_outer(input)).parse2(quote!(#(#attrs)*))?;
+                if field_attr.has_constructor() || field_attr.has_getter() || field_attr.has_setter()
+                {
+                    return Err(Error::new(
+                        field.span(),
+                        "`constructor`, `getter` and `setter` are markers of methods. expected `get` or `set`",
+                    ));
+                }
+
+                let ty = if attr.has_repr("C") || field_attr.has_get() || field_attr.has_set() {
+                    parse_type.parse2(field.ty.into_token_stream())?
+                } else {
+                    Type::Unsupported(field.ty)
+                };
+                Ok(Field {
+                    attr: field_attr,
+                    ident: field.ident,
+                    ty,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            attr,
+            ident,
+            fields,
+        })
+    }
+
+    /// The field accessors of this struct with their name in typescript. The
+    /// fields of a tuple struct are named `_0`, `_1`, ...
+    ///
+    /// - `get_x(&self) -> X` for copyable fields, `-> &X` otherwise
+    /// - `set_x(&mut self, X)`
+    pub fn accessors(&self) -> Vec<(Ident, ItemFn)> {
+        let self_ty = Type::UserDefined(self.ident.clone());
+
+        let mut items = Vec::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            let name = field_name(field, i);
+            let ts_name = match &field.ident {
+                Some(ident) => ident.clone(),
+                None => format_ident!("_{i}"),
+            };
+            if field.attr.has_get() {
+                let output = if field.ty.is_copy() {
+                    field.ty.clone()
+                } else {
+                    Type::Ref(Box::new(field.ty.clone()))
+                };
+                items.push((
+                    ts_name.clone(),
+                    self.accessor(
+                        Marker::Get,
+                        &field.attr,
+                        &format!("get_{name}"),
+                        vec![Type::Ref(Box::new(self_ty.clone()))],
+                        output,
+                    ),
+                ));
+            }
+            if field.attr.has_set() {
+                items.push((
+                    ts_name,
+                    self.accessor(
+                        Marker::Set,
+                        &field.attr,
+                        &format!("set_{name}"),
+                        vec![Type::RefMut(Box::new(self_ty.clone())), field.ty.clone()],
+                        Type::Void,
+                    ),
+                ));
+            }
+        }
+        items
+    }
+
+    /// An accessor of a field, documented by the doc comments of the field
+    fn accessor(
+        &self,
+        marker: Marker,
+        field_attr: &Attribute,
+        ident: &str,
+        inputs: Vec<Type>,
+        output: Type,
+    ) -> ItemFn {
+        let assoc = match marker {
+            Marker::Set => Association::InstanceMut,
+            _ => Association::Instance,
+        };
+        // the accessors of a renamed field are renamed along with it
+        let mut markers = vec![marker];
+        markers.extend(
+            field_attr
+                .rename()
+                .map(|name| Marker::Rename(name.to_string())),
+        );
+        ItemFn {
+            attr: Attribute {
+                markers,
+                doc: field_attr.doc.clone(),
+                deprecated: field_attr.deprecated.clone(),
+                ..Default::default()
+            },
+            vis: Visibility::Public(Default::default()),
+            const_: None,
+            async_: None,
+            unsafe_: None,
+            ident: format_ident!("{ident}"),
+            inputs,
+            output,
+            assoc: Some(assoc),
+            block: Block {
+                self_ty: Some(self.ident.clone()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// The rust implementation of the functions returned by [`Self::accessors`]
+    fn impl_accessors(&self) -> TokenStream {
+        let mut items = TokenStream::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            let name = field_name(field, i);
+            let member = match &field.ident {
+                Some(ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(syn::Index::from(i)),
+            };
+            let ty = &field.ty;
+            if field.attr.has_get() {
+                let get = format_ident!("get_{name}");
+                items.extend(if ty.is_copy() {
+                    quote! { pub fn #get(&self) -> #ty { self.#member } }
+                } else {
+                    quote! { pub fn #get(&self) -> &#ty { &self.#member } }
+                });
+            }
+            if field.attr.has_set() {
+                let set = format_ident!("set_{name}");
+                items.extend(quote! {
+                    pub fn #set(&mut self, value: #ty) { self.#member = value; }
+                });
+            }
+        }
+        items
+    }
+
+    /// Replaces the live `#[deno_bindgen(get, set)]` markers on the fields of a
+    /// struct with their inert form
+    pub fn inert_field_markers(input: TokenStream) -> Result<TokenStream> {
+        let mut item_struct: syn::ItemStruct = syn::parse2(input)?;
+        for field in item_struct.fields.iter_mut() {
+            Attribute::inert_markers(&mut field.attrs)?;
+        }
+        Ok(item_struct.into_token_stream())
+    }
+
+    /// Whether this struct is passed by value using deno's struct ffi type.
+    /// It must be `#[repr(C)]` and its fields must be numbers, booleans,
+    /// pointers or other structs passed by value
+    pub fn is_by_value(&self, structs: &StructTable) -> bool {
+        self.attr.has_repr("C")
+            && !self.fields.is_empty()
+            && self.fields.iter().all(|field| match &field.ty {
+                Type::Numeric(_) | Type::Bool | Type::Ptr(_) | Type::PtrMut(_) => true,
+                Type::UserDefined(ident) => structs.contains(ident),
+                _ => false,
+            })
+    }
+}
+
+/// The name of a field in the rust accessor functions, i.e. `x` or `0`
+fn field_name(field: &Field, index: usize) -> String {
+    match &field.ident {
+        Some(ident) => ident.to_string(),
+        None => index.to_string(),
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: struct table
+
+/// The structs of a crate that are passed by value. Function signatures only
+/// name these types, so the table is built by the cli from the expanded source
+/// code and passed to the macro through the [`StructTable::ENV`] environment
+/// variable
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StructTable {
+    store: BTreeSet<String>,
+}
+
+impl StructTable {
+    pub const ENV: &'static str = "DENO_BINDGEN2_STRUCTS";
+
+    /// Builds the struct table of a crate from its `deno_bindgen` items
+    pub fn collect(items: &[Item]) -> Self {
+        let mut item_structs = Vec::new();
+        Self::collect_items(items, &mut item_structs);
+
+        // a struct is only passed by value if the structs of its fields are as
+        // well, so structs are added until none is left
+        let mut table = Self::default();
+        loop {
+            let len = table.store.len();
+            for item_struct in &item_structs {
+                if item_struct.is_by_value(&table) {
+                    table.store.insert(item_struct.ident.to_string());
+                }
+            }
+            if table.store.len() == len {
+                return table;
+            }
+        }
+    }
+
+    fn collect_items<'a>(items: &'a [Item], item_structs: &mut Vec<&'a ItemStruct>) {
+        for item in items {
+            match item {
+                Item::Struct(item_struct) => item_structs.push(item_struct),
+                Item::Mod(item_mod) => Self::collect_items(&item_mod.items, item_structs),
+                _ => (),
+            }
+        }
+    }
+
+    /// Reads the table passed by the cli. The table is empty when the crate is
+    /// not being built by the cli
+    #[cfg(feature = "macro")]
+    pub fn from_env() -> Self {
+        match env_var(Self::ENV) {
+            Ok(table) => Self::parse_env(&table),
+            Err(_) => Self::default(),
         }
+    }
+
+    /// Parses the table from its environment variable representation, one
+    /// struct name per line
+    pub fn parse_env(table: &str) -> Self {
+        let store = table.lines().map(|line| line.to_string()).collect();
+        Self { store }
+    }
 
-        // fields are ignored for now. in the future, they may be supported directly
-        Ok(Self { attr, ident })
+    pub fn to_env(&self) -> String {
+        self.store
+            .iter()
+            .map(|ident| format!("{ident}\n"))
+            .collect()
+    }
+
+    pub fn contains(&self, ident: &Ident) -> bool {
+        self.store.contains(&ident.to_string())
     }
 }
 
@@ -70,18 +344,25 @@ impl ItemStruct {
 #[cfg(test)]
 mod parse_tests {
     use super::*;
+    use crate::rust::{File, TypeNumeric};
 
     #[test]
     fn test_parse_struct() {
         dbg_assert!(parse_quote!(ItemStruct, struct CustomType;), ItemStruct {
-            attr:  Attribute::default(),
-            ident: format_ident!("CustomType"),
+            attr:   Attribute::default(),
+            ident:  format_ident!("CustomType"),
+            fields: Vec::new(),
         });
         dbg_assert!(
             parse_quote!(ItemStruct, struct CustomType(bool);),
             ItemStruct {
-                attr:  Attribute::default(),
-                ident: format_ident!("CustomType"),
+                attr:   Attribute::default(),
+                ident:  format_ident!("CustomType"),
+                fields: vec![Field {
+                    attr:  Attribute::default(),
+                    ident: None,
+                    ty:    Type::Unsupported(syn::parse_quote!(bool)),
+                }],
             }
         );
         dbg_assert!(
@@ -92,10 +373,142 @@ mod parse_tests {
                 }
             ),
             ItemStruct {
+                attr:   Attribute::default(),
+                ident:  format_ident!("CustomType"),
+                fields: vec![Field {
+                    attr:  Attribute::default(),
+                    ident: Some(format_ident!("some_field")),
+                    ty:    Type::Unsupported(syn::parse_quote!(bool)),
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_accessors() {
+        let item_struct = parse_quote!(
+            ItemStruct,
+            struct Counter {
+                #[deno_bindgen(get, set)]
+                count: u32,
+                #[doc = "deno_bindgen_get"]
+                name:  String,
+                other: std::collections::HashMap<u8, u8>,
+            }
+        );
+        dbg_assert!(item_struct.fields[0].attr.markers, vec![
+            Marker::Get,
+            Marker::Set
+        ]);
+        dbg_assert!(item_struct.fields[1].ty, Type::String);
+
+        let accessors: Vec<String> = item_struct
+            .accessors()
+            .iter()
+            .map(|(name, item)| format!("{name} {}", item.shim_ident()))
+            .collect();
+        dbg_assert!(accessors, vec![
+            "count __Counter__get_count",
+            "count __Counter__set_count",
+            "name __Counter__get_name",
+        ]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_unknown_field_marker() {
+        parse_quote!(
+            ItemStruct,
+            struct Counter {
+                #[deno_bindgen(get, frobnicate)]
+                count: u32,
+            }
+        );
+    }
+
+    #[test]
+    fn test_inert_field_markers() {
+        let input = ItemStruct::inert_field_markers(quote! {
+            struct Counter {
+                #[deno_bindgen(get, set)]
+                #[serde(skip)]
+                count: u32,
+            }
+        })
+        .unwrap();
+        let expected = quote! {
+            struct Counter {
+                #[serde(skip)]
+                #[cfg_attr(not(deno_bindgen), doc = "deno_bindgen_get")]
+                #[cfg_attr(not(deno_bindgen), doc = "deno_bindgen_set")]
+                count: u32,
+            }
+        };
+        dbg_assert!(input.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_parse_repr_c() {
+        let item_struct = parse_quote!(
+            ItemStruct,
+            #[repr(C)]
+            struct Point {
+                x: f64,
+                y: f64,
+            }
+        );
+        dbg_assert!(item_struct.fields, vec![
+            Field {
+                attr:  Attribute::default(),
+                ident: Some(format_ident!("x")),
+                ty:    Type::Numeric(TypeNumeric::F64),
+            },
+            Field {
                 attr:  Attribute::default(),
-                ident: format_ident!("CustomType"),
+                ident: Some(format_ident!("y")),
+                ty:    Type::Numeric(TypeNumeric::F64),
+            },
+        ]);
+
+        let item_struct = parse_quote!(
+            ItemStruct,
+            #[repr(C)]
+            struct Pair(u8, *const u8);
+        );
+        dbg_assert!(item_struct.fields.len(), 2);
+        dbg_assert!(item_struct.fields[0].ident, None);
+    }
+
+    #[test]
+    fn test_struct_table() {
+        let file = File::parse_str(
+            quote! {
+                #[doc = "deno_bindgen"]
+                #[repr(C)]
+                struct Line(Point, Point);
+
+                #[doc = "deno_bindgen"]
+                #[repr(C)]
+                struct Point { x: f64, y: f64 }
+
+                #[doc = "deno_bindgen"]
+                #[repr(C)]
+                struct Named { name: String }
+
+                #[doc = "deno_bindgen"]
+                #[repr(C)]
+                struct Nested(Named);
+
+                #[doc = "deno_bindgen"]
+                struct Opaque { x: f64 }
             }
+            .to_string()
+            .as_str(),
         );
+
+        let table = StructTable::collect(&file.items);
+        dbg_assert!(table.to_env(), "Line\nPoint\n");
+        dbg_assert!(StructTable::parse_env(&table.to_env()), table);
     }
 
     #[test]
@@ -104,8 +517,9 @@ mod parse_tests {
         dbg_assert!(
             parse_quote!(ItemStruct, struct CustomType<T>(T);),
             ItemStruct {
-                attr:  Attribute::default(),
-                ident: format_ident!("CustomType"),
+                attr:   Attribute::default(),
+                ident:  format_ident!("CustomType"),
+                fields: Vec::new(),
             }
         );
     }
@@ -124,8 +538,9 @@ mod parse_tests {
                 }
             ),
             ItemStruct {
-                attr:  Attribute::default(),
-                ident: format_ident!("CustomType"),
+                attr:   Attribute::default(),
+                ident:  format_ident!("CustomType"),
+                fields: Vec::new(),
             }
         );
     }
@@ -139,13 +554,32 @@ impl ToTokens for ItemStruct {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let self_ty = &self.ident;
         let ident = format_ident!("__{}__drop", self.ident);
+        let export_drop = export_name(&ident, false);
         tokens.extend(quote! {
             impl deno_bindgen2::DenoBindgen for #self_ty {}
-            #[unsafe(no_mangle)]
+            #export_drop
             extern "C" fn #ident (arg_0: *mut #self_ty) {
                 std::mem::drop(Box::from(arg_0));
             }
         });
+
+        let mut accessors: Vec<ItemFn> =
+            self.accessors().into_iter().map(|(_, item)| item).collect();
+        if !accessors.is_empty() {
+            for item in &mut accessors {
+                item.transform();
+            }
+            let impl_accessors = self.impl_accessors();
+            tokens.extend(quote! {
+                const _: () = {
+                    impl #self_ty {
+                        #impl_accessors
+                    }
+
+                    #(#accessors)*
+                };
+            });
+        }
     }
 }
 
@@ -162,7 +596,10 @@ mod print_tests {
         let raw = parse_quote!(
             ItemStruct,
             pub struct CustomType {
+                #[deno_bindgen(get, set)]
                 field: bool,
+                #[deno_bindgen(get)]
+                name:  String,
             }
         );
         println!(
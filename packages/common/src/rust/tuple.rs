@@ -0,0 +1,85 @@
+use crate::rust::util::*;
+use crate::rust::{assoc_fn, ItemFn, Type};
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: tuple api
+
+/// An interned tuple type, identified by its mangled name. Its accessor
+/// functions are emitted once for the whole crate as associated functions of
+/// a unit struct of the same name
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemTuple {
+    pub ident: Ident,
+    pub elems: Vec<Type>,
+}
+
+impl ItemTuple {
+    pub fn new(tuple: &Type) -> Self {
+        match tuple {
+            Type::Tuple(elems) => Self {
+                ident: format_ident!("{}", tuple.mangle()),
+                elems: elems.clone(),
+            },
+            _ => panic!("attempted to intern a type that is not a tuple"),
+        }
+    }
+
+    /// The element accessors, constructor and destructor of this tuple type,
+    /// in the same order they are emitted by the macro and the cli
+    ///
+    /// - `new(A, B) -> (A, B)`
+    /// - `get_0(&(A, B)) -> A` for copyable elements, `-> &A` otherwise
+    /// - `drop((A, B))`
+    pub fn items(&self) -> Vec<ItemFn> {
+        let ident = &self.ident;
+        let tuple = Type::Tuple(self.elems.clone());
+
+        let mut items = vec![assoc_fn(ident, "new", self.elems.clone(), tuple.clone())];
+        for (i, elem) in self.elems.iter().enumerate() {
+            let output = if elem.is_copy() {
+                elem.clone()
+            } else {
+                Type::Ref(Box::new(elem.clone()))
+            };
+            items.push(assoc_fn(
+                ident,
+                &format!("get_{i}"),
+                vec![Type::Ref(Box::new(tuple.clone()))],
+                output,
+            ));
+        }
+        items.push(assoc_fn(ident, "drop", vec![tuple], Type::Void));
+        items
+    }
+
+    /// The rust implementation of the functions returned by [`Self::items`]
+    pub fn impl_items(&self) -> TokenStream {
+        let tuple = Type::Tuple(self.elems.clone());
+
+        let args: Vec<Ident> = (0..self.elems.len())
+            .map(|i| format_ident!("arg_{i}"))
+            .collect();
+        let elems = &self.elems;
+
+        let getters = self.elems.iter().enumerate().map(|(i, elem)| {
+            let get = format_ident!("get_{i}");
+            let index = syn::Index::from(i);
+            if elem.is_copy() {
+                quote! { pub fn #get(tuple: &#tuple) -> #elem { tuple.#index } }
+            } else {
+                quote! { pub fn #get(tuple: &#tuple) -> &#elem { &tuple.#index } }
+            }
+        });
+
+        quote! {
+            pub fn new(#(#args: #elems),*) -> #tuple {
+                (#(#args,)*)
+            }
+            #(#getters)*
+            pub fn drop(tuple: #tuple) {
+                std::mem::drop(tuple);
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::rust::util::*;
+use crate::rust::{EnumTable, StructTable};
 
 /* ---------------------------------------------------------------------------- */
 
@@ -63,6 +64,13 @@ pub enum Type {
     /// be made that enforces against function signatures
     FnPtr(syn::TypeBareFn),
 
+    /// `dyn Fn(A) -> R`, `dyn FnMut(A) -> R` or `dyn FnOnce(A) -> R`
+    ///
+    /// Only meaningful as a parameter behind a `Box<T>`, `&T` or `&mut T` (see
+    /// [`Type::closure`]). The shim takes an `extern "C"` callback and a context
+    /// pointer in its place, and passes a closure calling back into them
+    DynFn(TypeDynFn),
+
     /// `*const T`
     Ptr(Box<Type>),
 
@@ -81,6 +89,22 @@ pub enum Type {
     /// Box is just a smart pointer
     Box(Box<Type>),
 
+    /// `Option<T>`
+    ///
+    /// Pointer-like elements (`Box<T>`, `&T`, `&mut T`) are represented as a
+    /// nullable pointer. Numeric elements, `bool` and `char` use a tagged
+    /// representation, where a boolean tag is returned and the value is
+    /// written to an out-parameter. Every other element is boxed behind a
+    /// nullable pointer
+    Option(Box<Type>),
+
+    /// `Result<T, E>`
+    ///
+    /// The error is converted into a string through its `Display`
+    /// implementation and returned as a nullable pointer, while the value is
+    /// written to an out-parameter
+    Result(Box<Type>, Box<Type>),
+
     /* -------------------------------------------- */
 
     // MARK: aggregates
@@ -104,6 +128,10 @@ pub enum Type {
     Array(TypeArray),
 
     /// `Vec<T>`
+    ///
+    /// Like tuples, every unique `Vec<T>` type is interned by the cli, which
+    /// emits `__RustVec_<T>__new/len/get/push/drop` accessor symbols for it.
+    /// `Vec<T>` with borrowed or unsized elements remain opaque
     Vec(Box<Type>),
 
     /// User-defined types should have some enforcement of being only defined
@@ -113,6 +141,19 @@ pub enum Type {
     /// trait checking mechanism
     UserDefined(Ident),
 
+    /// A `#[repr(C)]` user-defined struct passed by value through deno's struct
+    /// ffi type, instead of being boxed behind a pointer
+    ///
+    /// The macro cannot tell these apart from other user-defined types, so they
+    /// are resolved from [`Type::UserDefined`] using the struct table built by
+    /// the cli (see [`Type::resolve_structs`])
+    Struct(Ident),
+
+    /// A fieldless user-defined enum passed as the integer of its
+    /// `#[repr(...)]`, resolved from [`Type::UserDefined`] using the enum table
+    /// built by the cli (see [`Type::resolve_enums`])
+    Enum(Ident, TypeNumeric),
+
     /// A tuple type `(A, B, C)`
     ///
     /// Tuples should be avoided as each tuple type is interpreted as a unique
@@ -123,52 +164,27 @@ pub enum Type {
     /// elements of a tuple, and map each tuple type into a vtable for enforcing
     /// type safety and which valid interaces or symbols they may be used with
     /// as a parameter or result type
-
-    ///
-    /// [!ISSUE] Implementation for tuple type
-    /// ---
-    ///
-    /// To allow users to interact with each element of a tuple, some mechanism
-    /// should be created that interprets a tuple and creates a dereferencing
-    /// operation to return each element
     ///
-    /// However, if this were to be done on the procedural macro side, there
-    /// would be redundant instances or implementations of this tuple element
-    /// interaction interace
+    /// Procedural macros cannot identify whether other functions throughout
+    /// the rest of the code take the same tuple type, so emitting these
+    /// functions from the macro alone would create redundant symbols. Instead,
+    /// the cli parses the expanded source code, interns every unique tuple type
+    /// under a mangled name (see [`Type::mangle`]), and assigns each one to the
+    /// first shim that uses it. The table is passed to the macro on the final
+    /// build, which then emits the accessor symbols only once:
     ///
     /// ```ignore
     /// #[deno_bindgen]
-    /// fn create_str(tup: (*mut u8, usize) ) -> *mut (*mut u8, usize)
-    /// // generated
-    /// extern "C" fn create_str_tup1(tup: (*mut u8, usize) ) -> *mut u8
-    /// extern "C" fn create_str_tup2(tup: (*mut u8, usize) ) -> usize
-    ///
-    /// #[deno_bindgen]
-    /// fn create_buffer(tup: (*mut u8, usize) ) -> *mut (*mut u8, usize)
-    /// // generated
-    /// extern "C" fn create_buffer_tup1(tup: (*mut u8, usize) ) -> *mut u8
-    /// extern "C" fn create_buffer_tup2(tup: (*mut u8, usize) ) -> usize
+    /// fn create_str() -> (u8, String)
+    /// // generated once for the `(u8, String)` tuple type
+    /// extern "C" fn __Tuple2_u8_String__new(arg_0: u8, arg_1: *mut String) -> *const (u8, String)
+    /// extern "C" fn __Tuple2_u8_String__get_0(arg_0: *const (u8, String)) -> u8
+    /// extern "C" fn __Tuple2_u8_String__get_1(arg_0: *const (u8, String)) -> *const String
+    /// extern "C" fn __Tuple2_u8_String__drop(arg_0: *mut (u8, String))
     /// ```
     ///
-    /// Notice how implementing this as a procedural macro would create
-    /// redundant symbols, which could bloat the user's code. Procedural macros
-    /// cannot identify whether there are other functions that take the same
-    /// arity of that tuple throughout the rest of the code
-    ///
-    /// To avoid this, we need to implement some mechanism that reuses functions
-    /// or for interning. But to achieve this, we need to have visibility
-    /// throughout the entire source code, which is not possible in the context
-    /// of a procedural macro
-    ///
-    /// One approach for this is introducing an entirely new preprocessing step
-    /// which sits between the final macro expansion step and the actual
-    /// compilation process by rustc.
-    ///
-    /// This process will expand the user's source code, do some AST parsing to
-    /// identify unqiue tuples of the same tuple arity to a type table, assign
-    /// them a mangled type name or identity, and inserts the tuple utility
-    /// symbols automatically. This transformed source code may then be passed
-    /// to rustc directly as an input for the rest of the compilation process
+    /// Tuples with borrowed, unsized or unit elements are not interned and
+    /// remain opaque
     Tuple(Vec<Type>),
 
     /// A type currently unsupported or unrecognized by the FFI implementation,
@@ -202,6 +218,14 @@ pub struct TypeArray {
     pub len:  usize,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeDynFn {
+    pub inputs: Vec<Type>,
+    pub output: Box<Type>,
+    /// the trait object as written, including its other bounds
+    pub ty:     syn::TypeTraitObject,
+}
+
 /* ---------------------------------------------------------------------------- */
 
 // MARK: parse
@@ -319,6 +343,26 @@ impl Type {
                     input.advance_to(&fork);
                     return Ok(Type::Box(Box::new(ty)));
                 }
+            } else if first == 'O' && rest == "ption" {
+                if let Ok(ty) = GenericArgument::parse_single_with_self_ty(&fork, self_ty) {
+                    // `None` is passed as a null pointer, which cannot tell the
+                    // outer `None` from the inner one
+                    if let Type::Option(_) | Type::Result(..) = ty {
+                        return Err(Error::new(
+                            ident.span(),
+                            "an `Option` cannot hold another `Option` or a `Result`",
+                        ));
+                    }
+                    input.advance_to(&fork);
+                    return Ok(Type::Option(Box::new(ty)));
+                }
+            } else if first == 'R' && rest == "esult" {
+                if let Ok(args) = GenericArgument::parse_multiple(&fork, self_ty) {
+                    if let Ok([ok, err]) = <[Type; 2]>::try_from(args) {
+                        input.advance_to(&fork);
+                        return Ok(Type::Result(Box::new(ok), Box::new(err)));
+                    }
+                }
             } else if first == 'V' && rest == "ec" {
                 if let Ok(ty) = GenericArgument::parse_single_with_self_ty(&fork, self_ty) {
                     input.advance_to(&fork);
@@ -369,6 +413,15 @@ impl Type {
             return Ok(Self::FnPtr(input.parse()?));
         }
 
+        // CLOSURES dyn Fn(usize) -> ()
+        if input.peek(Token![dyn]) {
+            let fork = input.fork();
+            if let Some(dyn_fn) = TypeDynFn::parse(fork.parse()?, self_ty)? {
+                input.advance_to(&fork);
+                return Ok(Self::DynFn(dyn_fn));
+            }
+        }
+
 
         // Slice [T] or Array [T; n]
         if input.peek(Bracket) {
@@ -408,6 +461,9 @@ impl Type {
                     break;
                 }
                 content.parse::<Token![,]>()?;
+                if content.is_empty() {
+                    break;
+                }
             }
             return Ok(Self::Tuple(elems));
         }
@@ -421,8 +477,9 @@ impl Type {
         // to std__fs__File or __my_crate__myMod
         // convert double colon `::` to double underscore `__`
 
+        // diagnostics can only be emitted from within a macro
         #[cfg(feature = "macro")]
-        {
+        if proc_macro::is_available() {
             let diag = diag_warning!(ty, "unsupported type");
             let diag = diag.note("this type will be converted into an opaque pointer object and will appear as an `Unsupported` type, which may not be helpful");
             let diag = match &ty {
@@ -439,9 +496,244 @@ impl Type {
         Ok(Self::Unsupported(ty))
     }
 
+    /// Whether this type can be passed by value through a typed array buffer,
+    /// used for the tagged representation of `Option<T>`
+    pub fn is_buffer_elem(&self) -> bool {
+        matches!(self, Self::Numeric(_) | Self::Bool | Self::Char)
+    }
+
+    /// The element type of a borrowed numeric slice `&[N]` or `&mut [N]`, which
+    /// is passed without copying as a typed array buffer followed by its length
+    pub fn buffer_slice_elem(&self) -> Option<&Type> {
+        match self {
+            Self::Ref(elem) | Self::RefMut(elem) => match &**elem {
+                Self::Slice(elem) if matches!(**elem, Self::Numeric(_)) => Some(elem),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether this is a tuple or `Vec<T>` type whose accessor symbols are
+    /// interned by the cli. Types with borrowed, unsized or unit elements are
+    /// left opaque
+    pub fn is_interned(&self) -> bool {
+        fn is_owned(elem: &Type) -> bool {
+            !matches!(
+                elem,
+                Type::Void | Type::Ref(_) | Type::RefMut(_) | Type::Str | Type::Slice(_)
+            )
+        }
+
+        match self {
+            Self::Tuple(elems) => elems.iter().all(is_owned),
+            Self::Vec(elem) => is_owned(elem),
+            _ => false,
+        }
+    }
+
+    /// Whether this type is returned by value from the element accessors of
+    /// interned types. Other types are returned by reference
+    pub fn is_copy(&self) -> bool {
+        matches!(
+            self,
+            Self::Numeric(_)
+                | Self::Bool
+                | Self::Char
+                | Self::Ptr(_)
+                | Self::PtrMut(_)
+                | Self::FnPtr(_)
+        )
+    }
+
+    /// Collects every interned type reachable from this type, in the order
+    /// they appear. Duplicates are skipped
+    pub fn collect_interned(&self, interned: &mut Vec<Type>) {
+        if self.is_interned() && !interned.contains(self) {
+            interned.push(self.clone());
+        }
+        match self {
+            Self::Ptr(elem)
+            | Self::PtrMut(elem)
+            | Self::Ref(elem)
+            | Self::RefMut(elem)
+            | Self::Box(elem)
+            | Self::Option(elem)
+            | Self::Slice(elem)
+            | Self::Vec(elem)
+            | Self::Result(elem, _) => elem.collect_interned(interned),
+            Self::Tuple(elems) => {
+                for elem in elems {
+                    elem.collect_interned(interned);
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Resolves a parameter or result type naming a struct passed by value, or
+    /// a reference to one, into a [`Type::Struct`]
+    ///
+    /// These structs cannot be nested in other types, except behind pointers
+    /// and references where they remain opaque
+    pub fn resolve_structs(&mut self, structs: &StructTable) -> Result<()> {
+        match self {
+            Self::UserDefined(ident) if structs.contains(ident) => {
+                *self = Self::Struct(ident.clone());
+                Ok(())
+            },
+            Self::Ref(elem) => match &**elem {
+                Self::UserDefined(ident) if structs.contains(ident) => {
+                    **elem = Self::Struct(ident.clone());
+                    Ok(())
+                },
+                _ => self.check_structs(structs),
+            },
+            _ => self.check_structs(structs),
+        }
+    }
+
+    fn check_structs(&self, structs: &StructTable) -> Result<()> {
+        match self {
+            Self::UserDefined(ident) if structs.contains(ident) => Err(Error::new(
+                ident.span(),
+                format!("`{ident}` is passed by value and cannot be nested in another type"),
+            )),
+            Self::Ptr(elem) | Self::PtrMut(elem) | Self::Ref(elem) | Self::RefMut(elem) => {
+                match &**elem {
+                    Self::UserDefined(_) => Ok(()),
+                    elem => elem.check_structs(structs),
+                }
+            },
+            Self::Box(elem)
+            | Self::Option(elem)
+            | Self::Slice(elem)
+            | Self::Vec(elem)
+            | Self::Array(TypeArray { elem, .. }) => elem.check_structs(structs),
+            Self::Result(ok, err) => {
+                ok.check_structs(structs)?;
+                err.check_structs(structs)
+            },
+            Self::Tuple(elems) => elems
+                .iter()
+                .try_for_each(|elem| elem.check_structs(structs)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolves a parameter or result type naming a C-like enum into a
+    /// [`Type::Enum`]. These enums cannot be nested in other types
+    pub fn resolve_enums(&mut self, enums: &EnumTable) -> Result<()> {
+        if let Self::UserDefined(ident) = self {
+            if let Some(repr) = enums.get(ident) {
+                *self = Self::Enum(ident.clone(), repr);
+                return Ok(());
+            }
+        }
+        self.check_enums(enums)
+    }
+
+    fn check_enums(&self, enums: &EnumTable) -> Result<()> {
+        match self {
+            Self::UserDefined(ident) if enums.get(ident).is_some() => Err(Error::new(
+                ident.span(),
+                format!("`{ident}` is passed as an integer and cannot be nested in another type"),
+            )),
+            Self::Ptr(elem)
+            | Self::PtrMut(elem)
+            | Self::Ref(elem)
+            | Self::RefMut(elem)
+            | Self::Box(elem)
+            | Self::Option(elem)
+            | Self::Slice(elem)
+            | Self::Vec(elem)
+            | Self::Array(TypeArray { elem, .. }) => elem.check_enums(enums),
+            Self::Result(ok, err) => {
+                ok.check_enums(enums)?;
+                err.check_enums(enums)
+            },
+            Self::Tuple(elems) => elems.iter().try_for_each(|elem| elem.check_enums(enums)),
+            _ => Ok(()),
+        }
+    }
+
+    /// A name for this type that is a valid identifier, used for naming the
+    /// symbols of interned types like tuples. i.e. `(u8, Box<String>)` is
+    /// mangled into `Tuple2_u8_Box_String`
+    ///
+    /// Types without a readable name (function pointers, closures and
+    /// unsupported types) are named after a hash of their tokens
+    pub fn mangle(&self) -> String {
+        match self {
+            Self::Void => "unit".to_string(),
+            Self::Numeric(type_numeric) => {
+                let ident = type_numeric.to_token_stream().into_iter().last().unwrap();
+                ident.to_string()
+            },
+            Self::Bool => "bool".to_string(),
+            Self::Char => "char".to_string(),
+            Self::Ptr(elem) => format!("Ptr_{}", elem.mangle()),
+            Self::PtrMut(elem) => format!("PtrMut_{}", elem.mangle()),
+            Self::Ref(elem) => format!("Ref_{}", elem.mangle()),
+            Self::RefMut(elem) => format!("RefMut_{}", elem.mangle()),
+            Self::Box(elem) => format!("Box_{}", elem.mangle()),
+            Self::Option(elem) => format!("Option_{}", elem.mangle()),
+            Self::Result(ok, err) => format!("Result_{}_{}", ok.mangle(), err.mangle()),
+            Self::Str => "str".to_string(),
+            Self::String => "String".to_string(),
+            Self::Slice(elem) => format!("Slice_{}", elem.mangle()),
+            Self::Array(TypeArray { elem, len }) => format!("Array{len}_{}", elem.mangle()),
+            Self::Vec(elem) => format!("Vec_{}", elem.mangle()),
+            Self::UserDefined(ident) | Self::Struct(ident) | Self::Enum(ident, _) => {
+                ident.to_string()
+            },
+            Self::Tuple(elems) => {
+                let mut mangled = format!("Tuple{}", elems.len());
+                for elem in elems {
+                    mangled.push('_');
+                    mangled.push_str(&elem.mangle());
+                }
+                mangled
+            },
+            Self::FnPtr(_) | Self::DynFn(_) | Self::Unsupported(_) => {
+                // FNV-1a, which is stable across compiler versions unlike the
+                // std hasher
+                let mut hash: u32 = 0x811c9dc5;
+                for byte in self.to_token_stream().to_string().bytes() {
+                    hash ^= byte as u32;
+                    hash = hash.wrapping_mul(0x01000193);
+                }
+                format!("Opaque{hash:08x}")
+            },
+        }
+    }
+
+    /// The signature of a closure parameter `Box<dyn Fn(A) -> R>`,
+    /// `&dyn Fn(A) -> R` or `&mut dyn FnMut(A) -> R`
+    pub fn closure(&self) -> Option<&TypeDynFn> {
+        match self {
+            Self::Box(elem) | Self::Ref(elem) | Self::RefMut(elem) => match &**elem {
+                Self::DynFn(dyn_fn) => Some(dyn_fn),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether this is the `&CancellationToken` parameter of a `cancellable`
+    /// function
+    pub fn is_cancellation_token(&self) -> bool {
+        match self {
+            Self::Ref(elem) => {
+                matches!(&**elem, Self::UserDefined(ident) if ident == "CancellationToken")
+            },
+            _ => false,
+        }
+    }
+
     pub fn is_self_ty(&self, self_ty: &Ident) -> bool {
         match self {
-            Self::UserDefined(ty) => {
+            Self::UserDefined(ty) | Self::Struct(ty) | Self::Enum(ty, _) => {
                 if ty.to_string() == self_ty.to_string() {
                     true
                 } else {
@@ -479,8 +771,9 @@ impl GenericArgument {
         }
     }
 
-    // [!TODO] handle types with multiple generic arguments
-    // might be used in the future for types like HashMap<K, V>
+    // parse multiple generic arguments that are concrete types
+    // i.e. Result<usize, String>. might be used in the future for types like
+    // HashMap<K, V>
     pub fn parse_multiple(
         input: ParseStream,
         self_ty: Option<&Ident>,
@@ -488,26 +781,79 @@ impl GenericArgument {
         input.parse::<Option<Token![::]>>()?;
         input.parse::<Token![<]>()?;
         let mut args = Vec::new();
-        let fork = input.fork();
         loop {
-            if fork.peek(Token![>]) {
-                break;
-            }
+            let fork = input.fork();
             if let Ok(ty) = Type::parse(&fork, self_ty) {
+                input.advance_to(&fork);
                 args.push(ty);
             } else {
                 return Err(fork.error("unsupported generic argument"));
             }
-            if fork.peek(Token![>]) {
+            if input.peek(Token![>]) {
                 break;
             }
             input.parse::<Token![,]>()?;
         }
-        input.advance_to(&fork);
+        input.parse::<Token![>]>()?;
         Ok(args)
     }
 }
 
+impl TypeDynFn {
+    /// Parses the signature of a trait object bounded by `Fn`, `FnMut` or
+    /// `FnOnce`. Returns `None` for other trait objects, which are left
+    /// unsupported
+    ///
+    /// The parameters and result are passed through a javascript callback, so
+    /// they are restricted to numeric, `bool` and raw pointer types
+    fn parse(mut ty: syn::TypeTraitObject, self_ty: Option<&Ident>) -> Result<Option<Self>> {
+        let Some(args) = ty.bounds.iter().find_map(|bound| match bound {
+            syn::TypeParamBound::Trait(bound) => {
+                let segment = bound.path.segments.last()?;
+                match (&segment.arguments, segment.ident.to_string().as_str()) {
+                    (syn::PathArguments::Parenthesized(args), "Fn" | "FnMut" | "FnOnce") => {
+                        Some(args.clone())
+                    },
+                    _ => None,
+                }
+            },
+            _ => None,
+        }) else {
+            return Ok(None);
+        };
+
+        let parse_type = |ty: &syn::Type| {
+            let parser = |input: ParseStream| Type::parse(input, self_ty);
+            match syn::parse::Parser::parse2(parser, ty.to_token_stream())? {
+                ty @ (Type::Numeric(_) | Type::Bool | Type::Ptr(_) | Type::PtrMut(_)) => Ok(ty),
+                _ => Err(Error::new(
+                    ty.span(),
+                    "unsupported closure parameter or result type\nnote: only numeric, `bool` and raw pointer types can be passed through a callback",
+                )),
+            }
+        };
+
+        let inputs = args.inputs.iter().map(parse_type).collect::<Result<_>>()?;
+        let output = match &args.output {
+            syn::ReturnType::Default => Type::Void,
+            syn::ReturnType::Type(_, output) => match &**output {
+                syn::Type::Tuple(tuple) if tuple.elems.is_empty() => Type::Void,
+                output => parse_type(output)?,
+            },
+        };
+
+        if let Some(self_ty) = self_ty {
+            TransformSelfType { self_ty }.visit_type_trait_object_mut(&mut ty);
+        }
+
+        Ok(Some(Self {
+            inputs,
+            output: Box::new(output),
+            ty,
+        }))
+    }
+}
+
 /// there is no way to access a rust 'object's symbols outside rust. we need to
 /// create a shim that bridges invocations for an associated function
 /// http://jakegoulding.com/rust-ffi-omnibus/objects/
@@ -710,6 +1056,86 @@ mod parse_tests {
         );
     }
 
+    #[test]
+    fn test_option() {
+        dbg_assert!(
+            parse_quote!(Type, Option<u8>),
+            Type::Option(Box::new(Type::Numeric(TypeNumeric::U8)))
+        );
+        dbg_assert!(
+            parse_quote!(Type, Option<&CustomType>),
+            Type::Option(Box::new(Type::Ref(Box::new(Type::UserDefined(format_ident!(
+                "CustomType"
+            ))))))
+        );
+        dbg_assert!(
+            parse_quote!(Type, "CustomType", Option<Box<Self>>),
+            Type::Option(Box::new(Type::Box(Box::new(Type::UserDefined(format_ident!(
+                "CustomType"
+            ))))))
+        );
+    }
+
+    #[test]
+    fn test_result() {
+        dbg_assert!(
+            parse_quote!(Type, Result<u8, String>),
+            Type::Result(
+                Box::new(Type::Numeric(TypeNumeric::U8)),
+                Box::new(Type::String)
+            )
+        );
+        dbg_assert!(
+            parse_quote!(Type, "CustomType", Result<(), Self>),
+            Type::Result(
+                Box::new(Type::Void),
+                Box::new(Type::UserDefined(format_ident!("CustomType")))
+            )
+        );
+    }
+
+    #[test]
+    fn test_closure() {
+        let closure = parse_quote!(Type, Box<dyn Fn(u32, *const u8) -> bool + Send>);
+        let dyn_fn = closure.closure().unwrap();
+        dbg_assert!(
+            dyn_fn.inputs,
+            vec![
+                Type::Numeric(TypeNumeric::U32),
+                Type::Ptr(Box::new(Type::Numeric(TypeNumeric::U8)))
+            ]
+        );
+        dbg_assert!(*dyn_fn.output, Type::Bool);
+
+        let closure = parse_quote!(Type, &mut dyn FnMut(f64));
+        dbg_assert!(*closure.closure().unwrap().output, Type::Void);
+        dbg_assert!(
+            closure.to_token_stream().to_string(),
+            "& mut dyn FnMut (f64)"
+        );
+
+        // other trait objects remain unsupported
+        assert!(parse_quote!(Type, Box<dyn std::error::Error>).closure().is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_closure_unsupported_parameter() {
+        parse_quote!(Type, &dyn Fn(String));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nested_option() {
+        parse_quote!(Type, Option<Option<u32>>);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_option_result() {
+        parse_quote!(Type, Option<Result<u32, String>>);
+    }
+
     #[test]
     fn test_tuple() {
         dbg_assert!(
@@ -730,6 +1156,33 @@ mod parse_tests {
         );
     }
 
+    #[test]
+    fn test_tuple_mangle() {
+        dbg_assert!(parse_quote!(Type, (u8, String)).mangle(), "Tuple2_u8_String");
+        dbg_assert!(
+            parse_quote!(Type, (u8, Box<(usize, u8)>, Option<CustomType>)).mangle(),
+            "Tuple3_u8_Box_Tuple2_usize_u8_Option_CustomType"
+        );
+        dbg_assert!(parse_quote!(Type, (u8,)).mangle(), "Tuple1_u8");
+    }
+
+    #[test]
+    fn test_collect_interned() {
+        let mut interned = Vec::new();
+        parse_quote!(Type, Vec<(u8, Box<(usize, u8)>)>).collect_interned(&mut interned);
+        parse_quote!(Type, (usize, u8)).collect_interned(&mut interned);
+        parse_quote!(Type, (u8, &str)).collect_interned(&mut interned);
+        parse_quote!(Type, Vec<&str>).collect_interned(&mut interned);
+        dbg_assert!(
+            interned,
+            vec![
+                parse_quote!(Type, Vec<(u8, Box<(usize, u8)>)>),
+                parse_quote!(Type, (u8, Box<(usize, u8)>)),
+                parse_quote!(Type, (usize, u8)),
+            ]
+        );
+    }
+
     #[test]
     fn test_unsupported_path() {
         dbg_assert!(
@@ -771,11 +1224,17 @@ impl ToTokens for Type {
             Type::Bool => quote! { std::primitive::bool },
             Type::Char => quote! { std::primitive::char },
             Type::FnPtr(type_bare_fn) => type_bare_fn.to_token_stream(),
+            Type::DynFn(TypeDynFn { ty, .. }) => ty.to_token_stream(),
             Type::Ptr(elem) => quote! { *const #elem },
             Type::PtrMut(elem) => quote! { *mut #elem },
             Type::Ref(elem) => quote! { &#elem },
             Type::RefMut(elem) => quote! { &mut #elem },
             Type::Box(elem) => quote! { std::boxed::Box<#elem> },
+            Type::Option(elem) => quote! { std::option::Option<#elem> },
+            Type::Result(ok, err) => match &**ok {
+                Type::Void => quote! { std::result::Result<(), #err> },
+                ok => quote! { std::result::Result<#ok, #err> },
+            },
             Type::Str => quote! { std::primitive::str },
             Type::String => quote! { std::string::String },
             Type::Slice(elem) => quote! { [#elem] },
@@ -784,8 +1243,13 @@ impl ToTokens for Type {
                 quote! { [#elem; #len] }
             },
             Type::Vec(elem) => quote! { std::vec::Vec<#elem> },
-            Type::UserDefined(ident) => ident.to_token_stream(),
-            Type::Tuple(elems) => quote! { ( #(#elems),* ) },
+            Type::UserDefined(ident) | Type::Struct(ident) | Type::Enum(ident, _) => {
+                ident.to_token_stream()
+            },
+            Type::Tuple(elems) => match elems.as_slice() {
+                [elem] => quote! { ( #elem, ) },
+                elems => quote! { ( #(#elems),* ) },
+            },
             Type::Unsupported(ty) => ty.to_token_stream(),
         });
     }
@@ -865,6 +1329,17 @@ mod print_tests {
         test_print!({ Vec<Box<u8>> }, { std::vec::Vec<std::boxed::Box<std::primitive::u8>> });
     }
 
+    #[test]
+    fn test_option() {
+        test_print!({ Option<u8>       }, { std::option::Option<std::primitive::u8> });
+        test_print!({ Option<&mut u8>  }, { std::option::Option<&mut std::primitive::u8> });
+    }
+
+    #[test]
+    fn test_result() {
+        test_print!({ Result<u8, String> }, { std::result::Result<std::primitive::u8, std::string::String> });
+    }
+
     #[test]
     fn test_tuple() {
         test_print!({ (u8, String) }, { (std::primitive::u8, std::string::String) });
@@ -17,6 +17,32 @@ pub use crate::{dbg_assert, dbg_quote, parse_quote};
 #[allow(unused_imports)]
 pub use crate::{diag_warning, diagnostic};
 
+/// Whether this runs from within a macro rather than from the cli. The `macro`
+/// feature alone does not tell, as cargo unifies it into the builds of the cli
+/// that share a workspace with a crate depending on the macros
+pub fn in_macro() -> bool {
+    #[cfg(feature = "macro")]
+    {
+        proc_macro::is_available()
+    }
+    #[cfg(not(feature = "macro"))]
+    {
+        false
+    }
+}
+
+/// Reads an environment variable, tracked by the compiler when read from a
+/// macro. Outside of a macro, such as in the tests of a crate using the macros
+/// as functions, it is read from the process environment
+#[cfg(feature = "macro")]
+pub fn env_var(key: &str) -> std::result::Result<String, std::env::VarError> {
+    if proc_macro::is_available() {
+        proc_macro::tracked::env_var(key)
+    } else {
+        std::env::var(key)
+    }
+}
+
 #[macro_export]
 macro_rules! parse_quote {
     ( $ty:ty, $($tt:tt)* ) => {
@@ -0,0 +1,89 @@
+use crate::rust::util::*;
+use crate::rust::{assoc_fn, ItemFn, Type, TypeNumeric};
+
+/* -------------------------------------------------------------------------- */
+
+// MARK: vec api
+
+/// An interned `Vec<T>` type, monomorphized for its element type. Its accessor
+/// functions are emitted once for the whole crate as associated functions of
+/// a unit struct named `RustVec_<T>`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemVec {
+    pub ident: Ident,
+    pub elem:  Type,
+}
+
+impl ItemVec {
+    pub fn new(vec: &Type) -> Self {
+        match vec {
+            Type::Vec(elem) => Self {
+                ident: format_ident!("RustVec_{}", elem.mangle()),
+                elem:  (**elem).clone(),
+            },
+            _ => panic!("attempted to intern a type that is not a `Vec<T>`"),
+        }
+    }
+
+    /// The accessors, constructor and destructor of this vec type, in the same
+    /// order they are emitted by the macro and the cli
+    ///
+    /// - `new() -> Vec<T>`
+    /// - `len(&Vec<T>) -> usize`
+    /// - `get(&Vec<T>, usize) -> T` for copyable elements, `-> &T` otherwise
+    /// - `push(&mut Vec<T>, T)`
+    /// - `drop(Vec<T>)`
+    pub fn items(&self) -> Vec<ItemFn> {
+        let ident = &self.ident;
+        let vec = Type::Vec(Box::new(self.elem.clone()));
+        let vec_ref = Type::Ref(Box::new(vec.clone()));
+        let usize = Type::Numeric(TypeNumeric::Usize);
+
+        let elem = if self.elem.is_copy() {
+            self.elem.clone()
+        } else {
+            Type::Ref(Box::new(self.elem.clone()))
+        };
+
+        vec![
+            assoc_fn(ident, "new", vec![], vec.clone()),
+            assoc_fn(ident, "len", vec![vec_ref.clone()], usize.clone()),
+            assoc_fn(ident, "get", vec![vec_ref, usize], elem),
+            assoc_fn(
+                ident,
+                "push",
+                vec![Type::RefMut(Box::new(vec.clone())), self.elem.clone()],
+                Type::Void,
+            ),
+            assoc_fn(ident, "drop", vec![vec], Type::Void),
+        ]
+    }
+
+    /// The rust implementation of the functions returned by [`Self::items`]
+    pub fn impl_items(&self) -> TokenStream {
+        let elem = &self.elem;
+        let vec = Type::Vec(Box::new(elem.clone()));
+
+        let get = if elem.is_copy() {
+            quote! { pub fn get(vec: &#vec, index: usize) -> #elem { vec[index] } }
+        } else {
+            quote! { pub fn get(vec: &#vec, index: usize) -> &#elem { &vec[index] } }
+        };
+
+        quote! {
+            pub fn new() -> #vec {
+                std::vec::Vec::new()
+            }
+            pub fn len(vec: &#vec) -> usize {
+                vec.len()
+            }
+            #get
+            pub fn push(vec: &mut #vec, value: #elem) {
+                vec.push(value);
+            }
+            pub fn drop(vec: #vec) {
+                std::mem::drop(vec);
+            }
+        }
+    }
+}
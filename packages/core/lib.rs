@@ -13,3 +13,36 @@ pub static DENO_BINDGEN_PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[no_mangle]
 pub static DENO_BINDGEN_PKG_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Adapters of std traits onto the javascript protocols of a class. Called by
+/// the shims of `#[deno_bindgen] impl Trait for T` blocks
+#[doc(hidden)]
+pub mod protocol {
+    use std::fmt::Debug;
+
+    pub trait Inspect: Debug {
+        fn inspect(&self) -> String {
+            format!("{self:?}")
+        }
+    }
+    impl<T: Debug + ?Sized> Inspect for T {}
+
+    pub trait PartialCompare: PartialOrd {
+        /// `-1`, `0` or `1`, and `NaN` if the values cannot be compared
+        fn partial_compare(&self, other: &Self) -> f64 {
+            match self.partial_cmp(other) {
+                Some(ordering) => ordering as i8 as f64,
+                None => f64::NAN,
+            }
+        }
+    }
+    impl<T: PartialOrd + ?Sized> PartialCompare for T {}
+
+    pub trait Compare: Ord {
+        /// `-1`, `0` or `1`
+        fn compare(&self, other: &Self) -> i8 {
+            self.cmp(other) as i8
+        }
+    }
+    impl<T: Ord + ?Sized> Compare for T {}
+}
@@ -2,11 +2,16 @@ use deno_bindgen2_common::Marker;
 use proc_macro::TokenStream;
 
 #[proc_macro_attribute]
-pub fn deno_bindgen(_attr: TokenStream, input: TokenStream) -> TokenStream {
-    Marker::deno_bindgen(input.into()).into()
+pub fn deno_bindgen(attr: TokenStream, input: TokenStream) -> TokenStream {
+    Marker::deno_bindgen(attr.into(), input.into()).into()
 }
 
 #[proc_macro_attribute]
-pub fn non_blocking(_attr: TokenStream, input: TokenStream) -> TokenStream {
-    Marker::non_blocking(input)
+pub fn non_blocking(attr: TokenStream, input: TokenStream) -> TokenStream {
+    Marker::non_blocking(attr, input)
+}
+
+#[proc_macro_attribute]
+pub fn js_string(_attr: TokenStream, input: TokenStream) -> TokenStream {
+    Marker::js_string(input)
 }
@@ -5,6 +5,7 @@ fn test_1() {
     println!("Hello, world!");
 }
 
+/// Greets the caller from rust
 #[deno_bindgen]
 fn test_2(string: String) -> String {
     format!("{} to Rust!", string)
@@ -62,6 +63,32 @@ fn test_fn_ptr(arg0: extern fn(u8) -> u8) -> extern fn(u8) -> u8 {
     arg0
 }
 
+#[deno_bindgen]
+fn test_callback(arg0: extern "C" fn(u32) -> u32, arg1: u32) -> u32 {
+    arg0(arg1)
+}
+
+#[deno_bindgen]
+fn test_closure(arg0: &dyn Fn(u32) -> u32, arg1: u32) -> u32 {
+    arg0(arg1)
+}
+
+#[deno_bindgen]
+#[non_blocking(cancellable)]
+fn test_cancellable(arg0: u32, token: &CancellationToken) -> u32 {
+    let mut count = 0;
+    while count < arg0 && !token.is_cancelled() {
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        count += 1;
+    }
+    count
+}
+
+#[deno_bindgen]
+async fn test_async(arg0: u32, arg1: String) -> String {
+    format!("{arg1} {arg0}")
+}
+
 #[deno_bindgen]
 fn test_str(arg0: &str) -> &str {
     arg0
@@ -72,11 +99,27 @@ fn test_string(arg0: String) -> String {
     arg0
 }
 
+#[deno_bindgen]
+#[js_string]
+fn test_js_string(arg0: &str, arg1: String) -> String {
+    format!("{arg0} {arg1}")
+}
+
 #[deno_bindgen]
 fn test_slice(arg0: &mut [u8]) -> &mut [u8] {
     arg0
 }
 
+#[deno_bindgen]
+fn test_buffer(arg0: &[f64]) -> f64 {
+    arg0.iter().sum()
+}
+
+#[deno_bindgen]
+fn test_buffer_mut(arg0: &mut [u8]) {
+    arg0.reverse();
+}
+
 #[deno_bindgen]
 fn test_array(arg0: [u8; 8]) -> [u8; 8] {
     arg0
@@ -87,6 +130,306 @@ fn test_vec(arg0: Vec<u8>) -> Vec<u8> {
     arg0
 }
 
+#[deno_bindgen]
+fn test_option(arg0: Option<u8>) -> Option<u8> {
+    arg0
+}
+
+#[deno_bindgen]
+fn test_option_box(arg0: Option<Box<u8>>) -> Option<Box<u8>> {
+    arg0
+}
+
+#[deno_bindgen]
+fn test_option_ref(arg0: Option<&u8>) -> Option<&u8> {
+    arg0
+}
+
+/// Fails on a zero value
+#[deno_bindgen]
+#[deprecated(note = "use `test_panic` instead")]
+fn test_result(arg0: u8) -> Result<u8, String> {
+    if arg0 > 0 {
+        Ok(arg0)
+    } else {
+        Err(String::from("expected a non-zero value"))
+    }
+}
+
+#[deno_bindgen]
+fn test_result_unit(arg0: bool) -> Result<(), String> {
+    arg0.then_some(()).ok_or(String::from("expected `true`"))
+}
+
+#[deno_bindgen]
+fn test_panic(arg0: u8) -> u8 {
+    if arg0 == 0 {
+        panic!("expected a non-zero value");
+    }
+    arg0
+}
+
+#[deno_bindgen]
+fn test_tuple(arg0: u8, arg1: String) -> (u8, String) {
+    (arg0, arg1)
+}
+
+#[deno_bindgen]
+fn test_tuple_nested(arg0: (u8, String)) -> Box<((u8, String), char)> {
+    Box::new((arg0, 'a'))
+}
+
+#[deno_bindgen]
+fn test_vec_string(arg0: Vec<u32>) -> Vec<String> {
+    arg0.iter().map(u32::to_string).collect()
+}
+
+/// A point on a plane, passed by value
+#[deno_bindgen]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Point {
+    /// horizontal coordinate
+    x: f64,
+    /// vertical coordinate
+    y: f64,
+}
+
+#[deno_bindgen]
+impl Point {
+    fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    /// Distance of the point from the origin
+    fn length(&self) -> f64 {
+        self.x.hypot(self.y)
+    }
+}
+
+#[deno_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+enum Direction {
+    Up = 1,
+    Down,
+    Left = 10,
+    Right,
+}
+
+#[deno_bindgen]
+fn test_enum(arg0: Direction) -> Direction {
+    match arg0 {
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+    }
+}
+
+#[deno_bindgen]
+enum Shape {
+    Circle { r: f64 },
+    Rect { w: f64, h: f64 },
+    Named(String),
+    Empty,
+}
+
+#[deno_bindgen]
+fn test_shape_area(arg0: &Shape) -> f64 {
+    match arg0 {
+        Shape::Circle { r } => std::f64::consts::PI * r * r,
+        Shape::Rect { w, h } => w * h,
+        Shape::Named(_) | Shape::Empty => 0.0,
+    }
+}
+
+/// A labelled counter
+#[deno_bindgen]
+struct Counter {
+    /// current value of the counter
+    #[deno_bindgen(get, set)]
+    count: u32,
+    #[deno_bindgen(get)]
+    label: String,
+}
+
+#[deno_bindgen]
+impl Counter {
+    #[deno_bindgen(constructor)]
+    fn new(label: String) -> Counter {
+        Counter { count: 0, label }
+    }
+
+    /// number of bytes of the label
+    #[deno_bindgen(getter)]
+    fn label_len(&self) -> usize {
+        self.label.len()
+    }
+
+    #[deno_bindgen(setter)]
+    fn set_label(&mut self, label: String) {
+        self.label = label;
+    }
+
+    #[non_blocking]
+    fn increment(&mut self, by: u32) -> u32 {
+        self.count += by;
+        self.count
+    }
+}
+
+#[deno_bindgen]
+impl std::fmt::Display for Counter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.label, self.count)
+    }
+}
+
+#[deno_bindgen]
+impl PartialEq for Counter {
+    fn eq(&self, other: &Self) -> bool {
+        self.count == other.count
+    }
+}
+
+#[deno_bindgen]
+impl PartialOrd for Counter {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.count.partial_cmp(&other.count)
+    }
+}
+
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+#[deno_bindgen]
+impl Describe for Counter {
+    fn describe(&self) -> String {
+        format!("counter `{}`", self.label)
+    }
+}
+
+#[deno_bindgen]
+struct Countdown {
+    remaining: u32,
+}
+
+#[deno_bindgen]
+impl Countdown {
+    fn new(from: u32) -> Countdown {
+        Countdown { remaining: from }
+    }
+}
+
+#[deno_bindgen]
+impl Iterator for Countdown {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let current = self.remaining.checked_sub(1)?;
+        self.remaining = current;
+        Some(current)
+    }
+}
+
+#[deno_bindgen]
+struct Emitter {
+    handlers: Vec<Box<dyn Fn(u32) + Send>>,
+}
+
+#[deno_bindgen]
+impl Emitter {
+    fn new() -> Emitter {
+        Emitter {
+            handlers: Vec::new(),
+        }
+    }
+
+    fn on(&mut self, handler: Box<dyn Fn(u32) + Send>) {
+        self.handlers.push(handler);
+    }
+
+    fn emit(&self, value: u32) {
+        for handler in &self.handlers {
+            handler(value);
+        }
+    }
+
+    async fn count(self) -> usize {
+        self.handlers.len()
+    }
+}
+
+#[deno_bindgen]
+fn test_struct(arg0: Point, arg1: &Point) -> Point {
+    Point {
+        x: arg0.x + arg1.x,
+        y: arg0.y + arg1.y,
+    }
+}
+
+/// Upper bound of a count
+#[deno_bindgen]
+const MAX_COUNT: u32 = 100;
+
+#[deno_bindgen]
+const MIN_OFFSET: i64 = -1;
+
+#[deno_bindgen]
+static GREETING: &'static str = "Hello from Rust!";
+
+#[deno_bindgen]
+static mut TOTAL: u64 = 0;
+
+#[deno_bindgen]
+const ORIGIN: Point = Point { x: 0.0, y: 0.0 };
+
+#[deno_bindgen]
+fn test_static(arg0: u64) -> u64 {
+    unsafe {
+        TOTAL += arg0;
+        TOTAL
+    }
+}
+
+/// A counter exported as `Tally`
+#[deno_bindgen(rename = "Tally")]
+struct RustTally {
+    #[deno_bindgen(get, rename = "total")]
+    tally_count: u32,
+}
+
+#[deno_bindgen]
+impl RustTally {
+    #[deno_bindgen(constructor)]
+    fn new() -> RustTally {
+        RustTally { tally_count: 0 }
+    }
+
+    #[deno_bindgen(rename = "add")]
+    fn add_one(&mut self) -> u32 {
+        self.tally_count += 1;
+        self.tally_count
+    }
+}
+
+#[deno_bindgen(rename = "tallyTotal")]
+fn tally_total(tally: &RustTally) -> u32 {
+    tally.tally_count
+}
+
+mod nested {
+    use deno_bindgen2::*;
+
+    // exported as `deno_bindgen2_test::nested::__test_nested`
+    #[deno_bindgen]
+    fn test_nested(arg0: u32) -> u32 {
+        arg0 * 2
+    }
+}
+
 // [!TODO] provide way to supress these kinds of warnings
 #[deno_bindgen]
 fn test_path(arg0: std::string::String) -> std::string::String {
@@ -1,4 +1,4 @@
-import { ValueMovedError } from "./util.ts";
+import { ValueBorrowedError, ValueMovedError } from "./util.ts";
 
 /**
  * Collection of types for representing Rust primitives and base classes/types
@@ -54,12 +54,54 @@ type RustPrimitive = number | bigint | boolean | void | RustChar;
  * user-defined types
  */
 abstract class RustPrototype<T = unknown> {
-    protected ptr: Deno.PointerValue<RustPrototype<T>> = null;
+    #ptr: Deno.PointerValue<RustPrototype<T>> = null;
+    #locked = false;
 
     constructor(ptr: Deno.PointerValue) {
         this.ptr = ptr! as Deno.PointerObject<RustPrototype<T>>;
     }
 
+    /**
+     * Wraps a pointer returned by Rust in an instance of this class. Bypasses
+     * the constructor of a class, which may call into Rust instead
+     */
+    static wrap<C>(this: abstract new (...args: never[]) => C, ptr: Deno.PointerValue): C {
+        return Reflect.construct(RustPrototype, [ptr], this);
+    }
+
+    /**
+     * The pointer to the Rust value. Throws while the value is locked by a
+     * non-blocking call
+     */
+    protected get ptr(): Deno.PointerValue<RustPrototype<T>> {
+        if (this.#locked) {
+            throw new ValueBorrowedError();
+        }
+        return this.#ptr;
+    }
+
+    protected set ptr(ptr: Deno.PointerValue<RustPrototype<T>>) {
+        this.#ptr = ptr;
+    }
+
+    /**
+     * Borrows the pointer for a non-blocking call, which may run on another
+     * thread. Any use of this object throws until `unlock()` is called once
+     * the call settles
+     */
+    protected lock() {
+        const ptr = this.ptr;
+        if (!ptr) {
+            throw new ValueMovedError();
+        }
+        this.#locked = true;
+        return ptr;
+    }
+
+    protected unlock() {
+        this.#locked = false;
+    }
+
     /**
      * Consumes the pointer and empties the contents of this object, making it
      * unusable. Any succeeding calls to this method will throw an error.
@@ -76,6 +118,68 @@ abstract class RustPrototype<T = unknown> {
     }
 }
 
+/**
+ * Keeps the callbacks of boxed Rust closures alive until Rust drops the
+ * closure. A callback is referenced while it is registered, which keeps the
+ * event loop alive. Use `unref()` to let the process exit while Rust still
+ * holds a callback
+ */
+class RustCallbacks {
+    static #callbacks = new Map<bigint, [Function, Deno.UnsafeCallback]>();
+    static #next = 0n;
+    static #release: Deno.UnsafeCallback | null = null;
+
+    /**
+     * Registers the callback wrapping a JavaScript function, returning the
+     * context pointer Rust releases it with
+     */
+    static register(fn: Function, callback: Deno.UnsafeCallback): Deno.PointerValue {
+        const ctx = ++RustCallbacks.#next;
+        RustCallbacks.#callbacks.set(ctx, [fn, callback]);
+        return Deno.UnsafePointer.create(ctx);
+    }
+
+    /**
+     * The function called by Rust with the context pointer of a dropped
+     * closure. It does not keep the event loop alive by itself
+     */
+    static get release(): Deno.PointerValue {
+        if (RustCallbacks.#release == null) {
+            RustCallbacks.#release = Deno.UnsafeCallback.threadSafe(
+                { parameters: ["pointer"], result: "void" } as const,
+                (ctx) => {
+                    const key = BigInt(Deno.UnsafePointer.value(ctx));
+                    const entry = RustCallbacks.#callbacks.get(key);
+                    if (entry) {
+                        RustCallbacks.#callbacks.delete(key);
+                        entry[1].close();
+                    }
+                },
+            );
+            RustCallbacks.#release.unref();
+        }
+        return RustCallbacks.#release.pointer;
+    }
+
+    /**
+     * Keeps the event loop alive while Rust holds a callback of this function
+     */
+    static ref(fn: Function) {
+        for (const [entry, callback] of RustCallbacks.#callbacks.values()) {
+            if (entry === fn) callback.ref();
+        }
+    }
+
+    /**
+     * Lets the process exit while Rust still holds a callback of this function
+     */
+    static unref(fn: Function) {
+        for (const [entry, callback] of RustCallbacks.#callbacks.values()) {
+            if (entry === fn) callback.unref();
+        }
+    }
+}
+
 type RustFnPtr<T extends string | null = null> = Deno.PointerObject<RustFnPtr<T>>;
 type RustPtr<T extends RustType | unknown = unknown> = Deno.PointerObject<RustPtr<T>>;
 type RustPtrMut<T extends RustType | unknown = unknown> = Deno.PointerObject<RustPtrMut<T>>;
@@ -105,8 +209,18 @@ class RustBox<T extends RustType> extends RustPrototype<RustBox<T>> {}
 class RustStr extends RustPrototype<RustStr> {}
 class RustString extends RustPrototype<RustString> {}
 class RustSlice<T extends RustType> extends RustPrototype<RustSlice<T>> {}
-class RustVec<T extends RustType> extends RustPrototype<RustVec<T>> {}
-class RustTuple<T extends RustType[]> extends RustPrototype<RustTuple<T>> {}
+class RustVec<T extends RustType> extends RustPrototype<RustVec<T>> {
+    // the accessors of the vec type are only used by the extended types
+    constructor(ptr: Deno.PointerValue, _vtable: unknown = null) {
+        super(ptr);
+    }
+}
+class RustTuple<T extends RustType[]> extends RustPrototype<RustTuple<T>> {
+    // the accessors of the tuple type are only used by the extended types
+    constructor(ptr: Deno.PointerValue, _vtable: unknown = null) {
+        super(ptr);
+    }
+}
 
 // <!-- deno-bindgen2-alt-type-end -->
 
@@ -135,6 +249,7 @@ export type {
 export {
     RustChar,
     RustPrototype,
+    RustCallbacks,
     RustBox,
     RustStr,
     RustString,
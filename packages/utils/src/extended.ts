@@ -1,4 +1,4 @@
-import type { RustRef, RustRefMut, RustType } from "./core.ts";
+import type { RustRef, RustRefMut, RustType, RustUsize } from "./core.ts";
 import { RustPrototype } from "./core.ts";
 import { ValueMovedError } from "./util.ts";
 
@@ -9,26 +9,32 @@ import { ValueMovedError } from "./util.ts";
 const { symbols } = Deno.dlopen("target/x86_64-unknown-linux-gnu/release/libdeno_bindgen2_utils.so", {
     // <!-- deno-bindgen2-ffi-symbols-start -->
     __RustString__new: {
+        name: "deno_bindgen2_utils::__RustString__new",
         parameters: [],
         result: "pointer",
     },
     __RustString__from: {
+        name: "deno_bindgen2_utils::__RustString__from",
         parameters: ["buffer", "u32"],
         result: "pointer",
     },
     __RustString__into_ptr: {
+        name: "deno_bindgen2_utils::__RustString__into_ptr",
         parameters: ["pointer"],
         result: "buffer",
     },
     __RustString__into_len: {
+        name: "deno_bindgen2_utils::__RustString__into_len",
         parameters: ["pointer"],
         result: "u32",
     },
     __RustString__push: {
+        name: "deno_bindgen2_utils::__RustString__push",
         parameters: ["pointer", "buffer", "u32"],
         result: "void",
     },
     __RustString__drop: {
+        name: "deno_bindgen2_utils::__RustString__drop",
         parameters: ["pointer"],
         result: "void",
     },
@@ -134,8 +140,189 @@ https://github.com/rust-lang/rust/issues/43467
 */
 // <!-- deno-bindgen2-ignore-end -->
 class RustSlice<T extends RustType> extends RustPrototype<RustSlice<T>> {}
-class RustVec<T extends RustType> extends RustPrototype<RustVec<T>> {}
-class RustTuple<T extends RustType[]> extends RustPrototype<RustTuple<T>> {}
+
+/**
+ * The accessor functions of a `Vec<T>` type. These are generated once for
+ * every element type used in the library
+ */
+type RustVecVTable<T extends RustType> = {
+    len(vec: RustRef<RustVec<T>>): RustUsize;
+    get(vec: RustRef<RustVec<T>>, index: RustUsize): RustElem<T>;
+    push(vec: RustRefMut<RustVec<T>>, value: T): void;
+    drop(vec: RustVec<T>): void;
+};
+
+/**
+ * A class interface for reading and appending to a Rust `Vec<T>`
+ */
+class RustVec<T extends RustType> extends RustPrototype<RustVec<T>> {
+    protected vtable: RustVecVTable<T> | null;
+
+    constructor(ptr: Deno.PointerValue, vtable: RustVecVTable<T> | null = null) {
+        super(ptr);
+        this.vtable = vtable;
+    }
+
+    protected borrow() {
+        if (this.ptr) {
+            const ptr = this.ptr as Deno.PointerObject;
+            return ptr as RustRef<RustVec<T>>;
+        } else {
+            throw new ValueMovedError();
+        }
+    }
+
+    protected borrow_mut(callback: (ref: RustRefMut<RustVec<T>>) => void) {
+        if (this.ptr) {
+            const ptr = this.ptr as Deno.PointerObject;
+            this.ptr = null;
+            try {
+                callback(ptr as RustRefMut<RustVec<T>>);
+            } finally {
+                this.ptr = ptr as Deno.PointerObject<RustPrototype<RustVec<T>>>;
+            }
+        } else {
+            throw new ValueMovedError();
+        }
+    }
+
+    protected accessors() {
+        if (this.vtable) {
+            return this.vtable;
+        } else {
+            throw new TypeError("this vec type has no accessor symbols");
+        }
+    }
+
+    /**
+     * The number of elements in this vec
+     */
+    get length() {
+        return Number(this.accessors().len(this.borrow()));
+    }
+
+    /**
+     * Get the element at the given index of this vec. Negative indices count
+     * back from the last element. Returns `undefined` if the index is out of
+     * bounds
+     */
+    at(index: number): RustElem<T> | undefined {
+        const length = this.length;
+        if (index < 0) {
+            index += length;
+        }
+        if (index < 0 || index >= length) {
+            return undefined;
+        }
+        return this.accessors().get(this.borrow(), BigInt(index));
+    }
+
+    /**
+     * Append an element to the end of this vec. Elements represented by a
+     * `RustPrototype` are moved into the vec
+     */
+    push(value: T) {
+        const vtable = this.accessors();
+        this.borrow_mut((ref_mut) => {
+            vtable.push(ref_mut, value);
+        });
+    }
+
+    *[Symbol.iterator](): Iterator<RustElem<T>> {
+        const length = this.length;
+        for (let i = 0; i < length; i++) {
+            yield this.accessors().get(this.borrow(), BigInt(i));
+        }
+    }
+
+    /**
+     * Get all the elements of this vec as an array
+     */
+    toArray(): RustElem<T>[] {
+        return [...this];
+    }
+
+    [Symbol.dispose]() {
+        if (this.ptr && this.vtable) {
+            this.vtable.drop(this);
+        }
+    }
+}
+
+/**
+ * An element of a tuple or vec as returned by its accessor. Elements
+ * represented by a `RustPrototype` are borrowed from their container
+ */
+type RustElem<T> = T extends RustPrototype ? RustRef<T> : T;
+
+/**
+ * The accessor functions of a tuple type. These are generated once for every
+ * unique tuple type in the library
+ */
+type RustTupleVTable<T extends RustType[]> = {
+    readonly arity: number;
+    drop(tuple: RustTuple<T>): void;
+} & {
+    [index: `get_${number}`]: (tuple: RustRef<RustTuple<T>>) => unknown;
+};
+
+/**
+ * A class interface for reading the elements of a Rust tuple
+ */
+class RustTuple<T extends RustType[]> extends RustPrototype<RustTuple<T>> {
+    protected vtable: RustTupleVTable<T> | null;
+
+    constructor(ptr: Deno.PointerValue, vtable: RustTupleVTable<T> | null = null) {
+        super(ptr);
+        this.vtable = vtable;
+    }
+
+    protected borrow() {
+        if (this.ptr) {
+            const ptr = this.ptr as Deno.PointerObject;
+            return ptr as RustRef<RustTuple<T>>;
+        } else {
+            throw new ValueMovedError();
+        }
+    }
+
+    protected accessors() {
+        if (this.vtable) {
+            return this.vtable;
+        } else {
+            throw new TypeError("this tuple type has no accessor symbols");
+        }
+    }
+
+    /**
+     * Get the element at the given index of this tuple
+     */
+    get<I extends number & keyof T>(index: I): RustElem<T[I]> {
+        const vtable = this.accessors();
+        if (index < 0 || index >= vtable.arity) {
+            throw new RangeError(`tuple index ${index} is out of bounds`);
+        }
+        return vtable[`get_${index}`](this.borrow()) as RustElem<T[I]>;
+    }
+
+    /**
+     * Get all the elements of this tuple as an array
+     */
+    toArray(): { [I in keyof T]: RustElem<T[I]> } {
+        const vtable = this.accessors();
+        const elems = [];
+        for (let i = 0; i < vtable.arity; i++) {
+            elems.push(vtable[`get_${i}`](this.borrow()));
+        }
+        return elems as { [I in keyof T]: RustElem<T[I]> };
+    }
+
+    [Symbol.dispose]() {
+        if (this.ptr && this.vtable) {
+            this.vtable.drop(this);
+        }
+    }
+}
 
 // <!-- deno-bindgen2-alt-type-end -->
 
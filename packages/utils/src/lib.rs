@@ -3,9 +3,15 @@
 
 use deno_bindgen2_macro::deno_bindgen;
 
+#[cfg(feature = "async")]
+mod runtime;
+#[cfg(feature = "async")]
+pub use runtime::RustTask;
+
 mod deno_bindgen2 {
     #[allow(dead_code)]
     pub(crate) trait DenoBindgen {}
+    pub(crate) use super::RustPanic;
 }
 
 struct Metadata;
@@ -51,3 +57,199 @@ impl RustString {
         std::mem::drop(string);
     }
 }
+
+#[allow(dead_code)]
+struct RustError;
+impl deno_bindgen2::DenoBindgen for RustError {}
+#[deno_bindgen]
+impl RustError {
+    /// frees the error message returned by a function with a `Result` type
+    pub unsafe fn drop(message: *mut u8) {
+        std::mem::drop(unsafe { std::ffi::CString::from_raw(message.cast()) });
+    }
+}
+
+#[allow(dead_code)]
+struct JsString;
+impl deno_bindgen2::DenoBindgen for JsString {}
+#[deno_bindgen]
+impl JsString {
+    /// frees a string returned by a function with the `js_string` marker
+    pub unsafe fn drop(ptr: *mut u8, len: usize) {
+        std::mem::drop(unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)) });
+    }
+}
+
+/// The context of a boxed closure that calls back into javascript. Captured by
+/// the closures generated for `Box<dyn Fn(..)>` parameters, and releases the
+/// javascript callback once the closure is dropped
+pub struct RustCallback {
+    ctx:     usize,
+    release: extern "C" fn(*const std::ffi::c_void),
+}
+
+impl RustCallback {
+    pub fn new(
+        ctx: *const std::ffi::c_void,
+        release: extern "C" fn(*const std::ffi::c_void),
+    ) -> Self {
+        Self {
+            ctx: ctx as usize,
+            release,
+        }
+    }
+    /// the context pointer passed back to the callback
+    pub fn ctx(&self) -> *const std::ffi::c_void {
+        self.ctx as *const std::ffi::c_void
+    }
+}
+
+impl Drop for RustCallback {
+    fn drop(&mut self) {
+        (self.release)(self.ctx());
+    }
+}
+
+/// The last parameter of a `#[non_blocking(cancellable)]` function, tripped
+/// once the `AbortSignal` passed by the caller is aborted. The function is
+/// expected to poll the token and return early once it is cancelled
+#[repr(transparent)]
+pub struct CancellationToken {
+    cancelled: std::sync::atomic::AtomicBool,
+}
+impl deno_bindgen2::DenoBindgen for CancellationToken {}
+
+impl CancellationToken {
+    /// whether the caller has aborted the call
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+#[deno_bindgen]
+impl CancellationToken {
+    /// trips the token of a running call. the token is a buffer owned by the
+    /// caller, which outlives the call
+    ///
+    /// # Safety
+    ///
+    /// `token` must point to a live `CancellationToken`
+    pub unsafe fn cancel(token: *const CancellationToken) {
+        let token = unsafe { &*token };
+        token
+            .cancelled
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+/// A pointer to an exported `const` or `static` item, exposed as a static
+/// symbol that the bindings read the current value of the item through
+#[repr(transparent)]
+pub struct RustStatic<T>(*const T);
+unsafe impl<T> Sync for RustStatic<T> {}
+
+impl<T> RustStatic<T> {
+    pub const fn new(ptr: *const T) -> Self {
+        Self(ptr)
+    }
+}
+
+/// Storage for the message of a panic caught at the ffi boundary. Used by the
+/// generated shims to surface panics as exceptions instead of aborting
+pub struct RustPanic;
+impl deno_bindgen2::DenoBindgen for RustPanic {}
+
+thread_local! {
+    static PANIC_MESSAGE: std::cell::RefCell<Option<std::ffi::CString>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+impl RustPanic {
+    /// stores the payload of a caught panic for the current thread
+    pub fn store(payload: Box<dyn std::any::Any + Send>) {
+        let message = Self::message(payload);
+        PANIC_MESSAGE.with(|cell| *cell.borrow_mut() = Some(message));
+    }
+
+    /// stores the payload of a caught panic in a slot owned by the caller, for
+    /// calls made from another thread. the message is freed with `drop`
+    ///
+    /// # Safety
+    ///
+    /// `slot` must be valid for writes
+    pub unsafe fn store_in(payload: Box<dyn std::any::Any + Send>, slot: *mut *mut u8) {
+        let message = Self::message(payload);
+        unsafe { slot.write(message.into_raw().cast()) };
+    }
+
+    fn message(payload: Box<dyn std::any::Any + Send>) -> std::ffi::CString {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            String::from("unknown panic payload")
+        };
+        std::ffi::CString::new(message.replace('\0', "")).unwrap()
+    }
+}
+
+#[deno_bindgen]
+impl RustPanic {
+    /// takes the message of the last panic caught on this thread, or null if
+    /// no panic occurred
+    pub fn take_message() -> *mut u8 {
+        PANIC_MESSAGE.with(|cell| match cell.borrow_mut().take() {
+            Some(message) => message.into_raw().cast(),
+            None => std::ptr::null_mut(),
+        })
+    }
+    /// frees a message returned by `take_message` or stored by `store_in`
+    ///
+    /// # Safety
+    ///
+    /// `message` must come from `take_message` or `store_in`, and not have
+    /// been freed already
+    pub unsafe fn drop(message: *mut u8) {
+        std::mem::drop(unsafe { std::ffi::CString::from_raw(message.cast()) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::RustPanic;
+
+    #[test]
+    fn test_panic_message() {
+        let message = |payload: Box<dyn std::any::Any + Send>| {
+            RustPanic::message(payload).into_string().unwrap()
+        };
+        assert_eq!(message(Box::new("borrowed")), "borrowed");
+        assert_eq!(message(Box::new(String::from("owned\0nul"))), "ownednul");
+        assert_eq!(message(Box::new(42)), "unknown panic payload");
+    }
+
+    #[test]
+    fn test_panic_store() {
+        assert!(RustPanic::take_message().is_null());
+        RustPanic::store(Box::new("first"));
+        RustPanic::store(Box::new("second"));
+
+        // only the last message is kept, and taken once
+        let message = RustPanic::take_message();
+        assert_eq!(unsafe { CStr::from_ptr(message.cast()) }, c"second");
+        unsafe { RustPanic::drop(message) };
+        assert!(RustPanic::take_message().is_null());
+    }
+
+    #[test]
+    fn test_panic_store_in() {
+        let mut slot = std::ptr::null_mut();
+        unsafe { RustPanic::store_in(Box::new("slot"), &mut slot) };
+        assert_eq!(unsafe { CStr::from_ptr(slot.cast()) }, c"slot");
+        unsafe { RustPanic::drop(slot) };
+        assert!(RustPanic::take_message().is_null());
+    }
+}
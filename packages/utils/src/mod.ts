@@ -14,18 +14,22 @@ const path = "target/x86_64-unknown-linux-gnu/release/libdeno_bindgen2_utils.so"
 
 export const { symbols } = Deno.dlopen(path, {
     __Metadata__rust_version: {
+        name: "deno_bindgen2_utils::__Metadata__rust_version",
         parameters: [],
         result: "buffer",
     },
     __Metadata__rust_toolchain: {
+        name: "deno_bindgen2_utils::__Metadata__rust_toolchain",
         parameters: [],
         result: "buffer",
     },
     __Metadata__lib_name: {
+        name: "deno_bindgen2_utils::__Metadata__lib_name",
         parameters: [],
         result: "buffer",
     },
     __Metadata__lib_version: {
+        name: "deno_bindgen2_utils::__Metadata__lib_version",
         parameters: [],
         result: "buffer",
     },
@@ -0,0 +1,127 @@
+//! A minimal executor driving the futures of `async` functions. Tasks are
+//! polled on a single background thread, or on a pool of one thread per core
+//! with the `async-pool` feature
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Wake, Waker};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+struct Task {
+    future: Mutex<Option<BoxFuture>>,
+}
+
+impl Task {
+    fn run(self: Arc<Self>) {
+        let mut slot = self.future.lock().unwrap();
+        if let Some(mut future) = slot.take() {
+            let waker = Waker::from(self.clone());
+            let mut cx = Context::from_waker(&waker);
+            if future.as_mut().poll(&mut cx).is_pending() {
+                *slot = Some(future);
+            }
+        }
+    }
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        // the queue is never closed, as the workers live as long as the process
+        queue().send(self).unwrap();
+    }
+}
+
+/// The queue of tasks ready to be polled. The workers are started on first use
+fn queue() -> &'static Sender<Arc<Task>> {
+    static QUEUE: OnceLock<Sender<Arc<Task>>> = OnceLock::new();
+    QUEUE.get_or_init(|| {
+        let workers = if cfg!(feature = "async-pool") {
+            std::thread::available_parallelism().map_or(1, |count| count.get())
+        } else {
+            1
+        };
+
+        let (sender, receiver) = channel::<Arc<Task>>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..workers {
+            let receiver = receiver.clone();
+            std::thread::Builder::new()
+                .name(format!("deno-bindgen2-worker-{i}"))
+                .spawn(move || loop {
+                    let task = receiver.lock().unwrap().recv();
+                    match task {
+                        Ok(task) => task.run(),
+                        Err(_) => break,
+                    }
+                })
+                .expect("failed to start a runtime worker");
+        }
+        sender
+    })
+}
+
+/// Polls a future, storing its output or the payload of its panic once it
+/// completes and notifying the caller
+struct Spawned<F: Future> {
+    future: Pin<Box<F>>,
+    result: Arc<Mutex<Option<std::thread::Result<F::Output>>>>,
+    done:   extern "C" fn(),
+}
+
+impl<F: Future> Future for Spawned<F> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let this = self.get_mut();
+        let poll = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            this.future.as_mut().poll(cx)
+        }));
+        let result = match poll {
+            Ok(Poll::Pending) => return Poll::Pending,
+            Ok(Poll::Ready(out)) => Ok(out),
+            Err(payload) => Err(payload),
+        };
+        *this.result.lock().unwrap() = Some(result);
+        (this.done)();
+        Poll::Ready(())
+    }
+}
+
+/// The future of an `async` function spawned on the runtime. Returned by the
+/// shim of the function and consumed by its `__await` shim once the task is
+/// done
+pub struct RustTask<T> {
+    result: Arc<Mutex<Option<std::thread::Result<T>>>>,
+}
+
+impl<T: Send + 'static> RustTask<T> {
+    /// spawns a future on the runtime. `done` is called from a worker thread
+    /// once the future completes or panics
+    pub fn spawn<F>(future: F, done: extern "C" fn()) -> *mut Self
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        let result = Arc::new(Mutex::new(None));
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(Spawned {
+                future: Box::pin(future),
+                result: result.clone(),
+                done,
+            }))),
+        });
+        task.wake();
+        Box::into_raw(Box::new(Self { result }))
+    }
+
+    /// takes the output of a finished task, resuming the panic of its future
+    pub fn take(self) -> T {
+        let result = self.result.lock().unwrap().take();
+        match result.expect("the task has not finished") {
+            Ok(out) => out,
+            Err(payload) => std::panic::resume_unwind(payload),
+        }
+    }
+}
@@ -6,9 +6,39 @@ class ValueMovedError extends Error {
     }
 }
 
+class ValueBorrowedError extends Error {
+    constructor() {
+        super("attempted to use a value while it is borrowed by a non-blocking call. await the call before using the value again. https://doc.rust-lang.org/error_codes/E0502.html");
+    }
+}
+
+/**
+ * An error returned by a Rust function through the `Err` variant of a
+ * `Result`, carrying the `Display` text of the error value. The bindings
+ * generate a subclass for each error type, i.e. `ParseIntError`
+ */
+class RustError extends Error {
+    constructor(message: string) {
+        super(message);
+        this.name = "RustError";
+    }
+}
+
+/**
+ * A panic caught at the boundary of a Rust function, carrying the panic
+ * message. The function's side effects up to the point of the panic are not
+ * rolled back
+ */
+class RustPanicError extends Error {
+    constructor(message: string) {
+        super(message);
+        this.name = "RustPanicError";
+    }
+}
+
 // <!-- deno-bindgen2-content-end -->
 
-export { ValueMovedError };
+export { RustError, RustPanicError, ValueBorrowedError, ValueMovedError };
 
 Deno.test("ValueMovedError", () => {
     throw new ValueMovedError();